@@ -0,0 +1,334 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+const BASE: u64 = 1 << 32;
+
+/* an arbitrary precision integer: a sign and little-endian base 2^32 digits. the digits never
+   end in a zero, so zero is the empty magnitude and is never negative */
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BigInt {
+    negative: bool,
+    digits:   Vec<u32>
+}
+
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt { negative: false, digits: vec![] }
+    }
+
+    pub fn one() -> BigInt {
+        BigInt { negative: false, digits: vec![1] }
+    }
+
+    pub fn from_i64(n: i64) -> BigInt {
+        BigInt::from_magnitude(n < 0, from_u64(n.unsigned_abs()))
+    }
+
+    fn from_magnitude(negative: bool, mut digits: Vec<u32>) -> BigInt {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        let negative = negative && !digits.is_empty();
+        BigInt { negative, digits }
+    }
+
+    /* a decimal integer with an optional sign */
+    pub fn parse(s: &str) -> Option<BigInt> {
+        let (negative, digits) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _          => (false, s)
+        };
+        if digits.is_empty() {
+            return None
+        }
+
+        let mut magnitude = vec![];
+        for c in digits.chars() {
+            let digit = c.to_digit(10)?;
+            magnitude = mul_small(&magnitude, 10);
+            magnitude = add_magnitudes(&magnitude, &[digit]);
+        }
+        Some(BigInt::from_magnitude(negative, magnitude))
+    }
+
+    /* None when it doesn't fit */
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None
+        }
+        let magnitude = self.digits.iter().rev().fold(0u64, |acc, &d| (acc << 32) | u64::from(d));
+        if self.negative {
+            if magnitude <= 1 << 63 { Some((magnitude as i64).wrapping_neg()) } else { None }
+        } else if magnitude <= i64::MAX as u64 {
+            Some(magnitude as i64)
+        } else {
+            None
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self.digits.iter().rev().fold(0.0, |acc, &d| acc * BASE as f64 + f64::from(d));
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::from_magnitude(false, self.digits.clone())
+    }
+
+    pub fn neg(&self) -> BigInt {
+        BigInt::from_magnitude(!self.negative, self.digits.clone())
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_magnitude(self.negative, add_magnitudes(&self.digits, &other.digits))
+        }
+
+        /* differing signs: subtract the smaller magnitude from the larger, which decides the sign */
+        match compare_magnitudes(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::from_magnitude(other.negative, sub_magnitudes(&other.digits, &self.digits)),
+            _              => BigInt::from_magnitude(self.negative, sub_magnitudes(&self.digits, &other.digits))
+        }
+    }
+
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        let mut product = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, &a) in self.digits.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.digits.iter().enumerate() {
+                let total      = u64::from(product[i + j]) + u64::from(a) * u64::from(b) + carry;
+                product[i + j] = total as u32;
+                carry          = total >> 32;
+            }
+            product[i + other.digits.len()] = carry as u32;
+        }
+        BigInt::from_magnitude(self.negative != other.negative, product)
+    }
+
+    /* truncating division, the remainder takes the sign of `self`. None when dividing by zero */
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None
+        }
+
+        let mut quotient  = vec![0u32; self.digits.len()];
+        let mut remainder = vec![];
+        for i in (0..self.digits.len() * 32).rev() {
+            remainder = mul_small(&remainder, 2);
+            if self.digits[i / 32] & (1 << (i % 32)) != 0 {
+                remainder = add_magnitudes(&remainder, &[1]);
+            }
+            if compare_magnitudes(&remainder, &other.digits) != Ordering::Less {
+                remainder        = sub_magnitudes(&remainder, &other.digits);
+                quotient[i / 32] |= 1 << (i % 32);
+            }
+        }
+
+        Some((BigInt::from_magnitude(self.negative != other.negative, quotient),
+              BigInt::from_magnitude(self.negative, remainder)))
+    }
+
+    /* the greatest common divisor, never negative */
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        while !b.is_zero() {
+            let (_, remainder) = a.div_rem(&b).unwrap();
+            a = b;
+            b = remainder;
+        }
+        a
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true)  => Ordering::Greater,
+            (true, false)  => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.digits, &other.digits),
+            (true, true)   => compare_magnitudes(&other.digits, &self.digits)
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0")
+        }
+
+        /* peel off nine decimal digits at a time, least significant first */
+        let mut chunks    = vec![];
+        let mut magnitude = self.digits.clone();
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = div_small(&magnitude, 1_000_000_000);
+            chunks.push(remainder);
+            magnitude = quotient;
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+fn from_u64(n: u64) -> Vec<u32> {
+    trim(vec![n as u32, (n >> 32) as u32])
+}
+
+fn trim(mut digits: Vec<u32>) -> Vec<u32> {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    digits
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum   = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let total = u64::from(*a.get(i).unwrap_or(&0)) + u64::from(*b.get(i).unwrap_or(&0)) + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    trim(sum)
+}
+
+/* `a` must be at least as large as `b` */
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow     = 0i64;
+    for (i, &digit) in a.iter().enumerate() {
+        let mut total = i64::from(digit) - i64::from(*b.get(i).unwrap_or(&0)) - borrow;
+        borrow = 0;
+        if total < 0 {
+            total += BASE as i64;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    trim(difference)
+}
+
+fn mul_small(a: &[u32], n: u32) -> Vec<u32> {
+    let mut product = Vec::with_capacity(a.len() + 1);
+    let mut carry   = 0u64;
+    for &digit in a {
+        let total = u64::from(digit) * u64::from(n) + carry;
+        product.push(total as u32);
+        carry = total >> 32;
+    }
+    product.push(carry as u32);
+    trim(product)
+}
+
+fn div_small(a: &[u32], n: u32) -> (Vec<u32>, u32) {
+    let mut quotient  = vec![0u32; a.len()];
+    let mut remainder = 0u64;
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) | u64::from(a[i]);
+        quotient[i] = (current / u64::from(n)) as u32;
+        remainder   = current % u64::from(n);
+    }
+    (trim(quotient), remainder as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        BigInt::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parse_display() {
+        assert_eq!(big("0").to_string(), "0");
+        assert_eq!(big("-0").to_string(), "0");
+        assert_eq!(big("+42").to_string(), "42");
+        assert_eq!(big("-1000000000000000000000").to_string(), "-1000000000000000000000");
+        assert_eq!(big("00012345678901234567890").to_string(), "12345678901234567890");
+        assert_eq!(BigInt::parse("12a"), None);
+        assert_eq!(BigInt::parse("-"), None);
+    }
+
+    #[test]
+    fn i64_round_trip() {
+        for &n in &[0, 1, -1, i64::MAX, i64::MIN, 1 << 32, -(1 << 40)] {
+            assert_eq!(BigInt::from_i64(n).to_i64(), Some(n));
+            assert_eq!(BigInt::from_i64(n).to_string(), n.to_string());
+        }
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(big("-9223372036854775809").to_i64(), None);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432109876543210");
+        assert_eq!(a.add(&b).to_string(), "-864197532086419753208641975320");
+        assert_eq!(a.sub(&b).to_string(), "1111111110111111111011111111100");
+        assert_eq!(a.mul(&b).to_string(), "-121932631137021795226185032733622923332237463801111263526900");
+        assert_eq!(a.sub(&a), BigInt::zero());
+    }
+
+    #[test]
+    fn div_rem() {
+        let a = big("121932631137021795226185032733622923332237463801111263526901");
+        let b = big("-987654321098765432109876543210");
+        let (quotient, remainder) = a.div_rem(&b).unwrap();
+        assert_eq!(quotient.to_string(), "-123456789012345678901234567890");
+        assert_eq!(remainder.to_string(), "1");
+        assert_eq!(big("-7").div_rem(&big("2")), Some((big("-3"), big("-1"))));
+        assert_eq!(a.div_rem(&BigInt::zero()), None);
+    }
+
+    #[test]
+    fn gcd() {
+        assert_eq!(big("12").gcd(&big("-18")), big("6"));
+        assert_eq!(big("0").gcd(&big("-5")), big("5"));
+        assert_eq!(big("36893488147419103232").gcd(&big("55340232221128654848")), big("18446744073709551616"));
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(big("-100000000000000000000") < big("-1"));
+        assert!(big("100000000000000000000") > big("99999999999999999999"));
+        assert!(big("-1") < BigInt::zero());
+    }
+
+    #[test]
+    fn to_f64() {
+        assert_eq!(big("-18446744073709551616").to_f64(), -18446744073709551616.0);
+    }
+}
@@ -0,0 +1,1153 @@
+use bigint::BigInt;
+use eval::apply;
+use eval::apply_one;
+use eval::Continuation;
+use eval::Environment;
+use eval::EvalError;
+use eval::Value;
+use eval::number::Number;
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+pub fn define_builtins(env: &mut Environment) {
+    env.define_primitive("+", add);
+    env.define_primitive("-", sub);
+    env.define_primitive("*", mul);
+    env.define_primitive("/", div);
+    env.define_primitive("=", num_eq);
+    env.define_primitive("<", lt);
+    env.define_primitive(">", gt);
+    env.define_primitive("<=", le);
+    env.define_primitive(">=", ge);
+    env.define_primitive("cons", cons);
+    env.define_primitive("car", car);
+    env.define_primitive("cdr", cdr);
+    env.define_primitive("set-car!", set_car);
+    env.define_primitive("set-cdr!", set_cdr);
+    env.define_primitive("list", list);
+    env.define_primitive("null?", is_null);
+    env.define_primitive("pair?", is_pair);
+    env.define_primitive("apply", apply_list);
+    env.define_primitive("map", map);
+    env.define_primitive("call/cc", call_cc);
+    env.define_primitive("call-with-current-continuation", call_cc);
+    env.define_primitive("values", values);
+    env.define_primitive("call-with-values", call_with_values);
+    env.define_primitive("for-each", for_each);
+    env.define_primitive("fold-left", fold_left);
+    env.define_primitive("fold-right", fold_right);
+    env.define_primitive("filter", filter);
+    env.define_primitive("number?", is_number);
+    env.define_primitive("integer?", is_integer);
+    env.define_primitive("odd?", is_odd);
+    env.define_primitive("even?", is_even);
+    env.define_primitive("string?", is_string);
+    env.define_primitive("symbol?", is_symbol);
+    env.define_primitive("procedure?", is_procedure);
+    env.define_primitive("boolean?", is_boolean);
+    env.define_primitive("not", not);
+    env.define_primitive("char?", is_char);
+    env.define_primitive("char->integer", char_to_integer);
+    env.define_primitive("integer->char", integer_to_char);
+    env.define_primitive("char-upcase", char_upcase);
+    env.define_primitive("char-downcase", char_downcase);
+    env.define_primitive("char=?", char_eq);
+    env.define_primitive("string-length", string_length);
+    env.define_primitive("string-append", string_append);
+    env.define_primitive("substring", substring);
+    env.define_primitive("string->symbol", string_to_symbol);
+    env.define_primitive("symbol->string", symbol_to_string);
+    env.define_primitive("string=?", string_eq);
+    env.define_primitive("vector", vector);
+    env.define_primitive("make-vector", make_vector);
+    env.define_primitive("vector?", is_vector);
+    env.define_primitive("vector-length", vector_length);
+    env.define_primitive("vector-ref", vector_ref);
+    env.define_primitive("vector-set!", vector_set);
+    env.define_primitive("error", error);
+    env.define_primitive("error-object?", is_error_object);
+    env.define_primitive("error-object-message", error_object_message);
+    env.define_primitive("error-object-irritants", error_object_irritants);
+    env.define_primitive("with-exception-handler", with_exception_handler);
+    env.define_primitive("eq?", is_eqv);
+    env.define_primitive("eqv?", is_eqv);
+    env.define_primitive("equal?", is_equal);
+    env.define_primitive("display", display);
+    env.define_primitive("write", write);
+    env.define_primitive("newline", newline);
+}
+
+fn numbers(args: &[Value]) -> Result<Vec<Number>, EvalError> {
+    args.iter().map(Number::from_value).collect()
+}
+
+fn at_least(args: &[Value], count: usize) -> Result<(), EvalError> {
+    if args.len() < count {
+        Err(EvalError::ArityMismatch { expected: count, got: args.len() })
+    } else {
+        Ok(())
+    }
+}
+
+fn exactly(args: &[Value], count: usize) -> Result<(), EvalError> {
+    if args.len() != count {
+        Err(EvalError::ArityMismatch { expected: count, got: args.len() })
+    } else {
+        Ok(())
+    }
+}
+
+fn add(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    let sum = numbers(args)?.into_iter().fold(Number::Integer(0), Number::add);
+    Ok(sum.into_value())
+}
+
+fn mul(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    let product = numbers(args)?.into_iter().fold(Number::Integer(1), Number::mul);
+    Ok(product.into_value())
+}
+
+/* (- x) negates, (- x y...) subtracts the rest from x */
+fn sub(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    at_least(args, 1)?;
+    let mut numbers = numbers(args)?.into_iter();
+    let first       = numbers.next().unwrap();
+    let result      = if args.len() == 1 {
+        Number::Integer(0).sub(first)
+    } else {
+        numbers.fold(first, Number::sub)
+    };
+    Ok(result.into_value())
+}
+
+/* (/ x) is the reciprocal, (/ x y...) divides x by the rest */
+fn div(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    at_least(args, 1)?;
+    let mut numbers = numbers(args)?.into_iter();
+    let first       = numbers.next().unwrap();
+    let mut result  = if args.len() == 1 { Number::Integer(1).div(first)? } else { first };
+    for n in numbers {
+        result = result.div(n)?;
+    }
+    Ok(result.into_value())
+}
+
+/* true when `test` holds for every adjacent pair of arguments */
+fn compare(args: &[Value], test: fn(Ordering) -> bool) -> Result<Value, EvalError> {
+    at_least(args, 1)?;
+    let numbers = numbers(args)?;
+    Ok(Value::Bool(numbers.windows(2).all(|pair| pair[0].compare(&pair[1]).is_some_and(test))))
+}
+
+fn num_eq(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    compare(args, |o| o == Ordering::Equal)
+}
+
+fn lt(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    compare(args, |o| o == Ordering::Less)
+}
+
+fn gt(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    compare(args, |o| o == Ordering::Greater)
+}
+
+fn le(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    compare(args, |o| o != Ordering::Greater)
+}
+
+fn ge(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    compare(args, |o| o != Ordering::Less)
+}
+
+fn cons(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 2)?;
+    Ok(Value::cons(args[0].clone(), args[1].clone()))
+}
+
+fn pair<'a>(value: &'a Value, name: &str) -> Result<&'a RefCell<(Value, Value)>, EvalError> {
+    match *value {
+        Value::Pair(ref pair) => Ok(pair),
+        ref other             => Err(EvalError::TypeError(format!("{}: not a pair: {:?}", name, other)))
+    }
+}
+
+fn car(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(pair(&args[0], "car")?.borrow().0.clone())
+}
+
+fn cdr(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(pair(&args[0], "cdr")?.borrow().1.clone())
+}
+
+/* (set-car! pair x) replaces the car of the pair itself, so everything holding it sees x */
+fn set_car(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 2)?;
+    pair(&args[0], "set-car!")?.borrow_mut().0 = args[1].clone();
+    Ok(Value::Nil)
+}
+
+/* (set-cdr! pair x) can make a list circular. printing, equal? and the builtins that take lists
+   all notice the cycle rather than following it forever */
+fn set_cdr(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 2)?;
+    pair(&args[0], "set-cdr!")?.borrow_mut().1 = args[1].clone();
+    Ok(Value::Nil)
+}
+
+fn list(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    Ok(Value::list(args.to_vec()))
+}
+
+fn is_null(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(args[0] == Value::Nil))
+}
+
+fn is_pair(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(matches!(args[0], Value::Pair(_))))
+}
+
+fn list_items(value: &Value, name: &str) -> Result<Vec<Value>, EvalError> {
+    value.list_to_vec().ok_or_else(|| EvalError::TypeError(format!("{}: not a list: {:?}", name, value)))
+}
+
+/* (apply f a b '(c d)) calls f with a, b, c and d */
+fn apply_list(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    at_least(args, 2)?;
+    let (last, init) = args[1..].split_last().unwrap();
+    let mut spread   = init.to_vec();
+    spread.extend(list_items(last, "apply")?);
+    apply(&args[0], spread, env)
+}
+
+/* call `procedure` across the lists in step, stopping at the end of the shortest */
+fn map_lists(name: &str, args: &[Value], env: &mut Environment) -> Result<Vec<Value>, EvalError> {
+    at_least(args, 2)?;
+    let lists  = args[1..].iter().map(|list| list_items(list, name)).collect::<Result<Vec<_>, _>>()?;
+    let length = lists.iter().map(Vec::len).min().unwrap_or(0);
+
+    (0..length).map(|i| {
+        let call_args = lists.iter().map(|list| list[i].clone()).collect();
+        apply_one(&args[0], call_args, env)
+    }).collect()
+}
+
+fn map(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    Ok(Value::list(map_lists("map", args, env)?))
+}
+
+fn for_each(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    map_lists("for-each", args, env)?;
+    Ok(Value::Nil)
+}
+
+/* (fold-left f init '(a b)) is (f (f init a) b) */
+fn fold_left(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 3)?;
+    let mut acc = args[1].clone();
+    for item in list_items(&args[2], "fold-left")? {
+        acc = apply_one(&args[0], vec![acc, item], env)?;
+    }
+    Ok(acc)
+}
+
+/* (fold-right f init '(a b)) is (f a (f b init)) */
+fn fold_right(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 3)?;
+    let mut acc = args[1].clone();
+    for item in list_items(&args[2], "fold-right")?.into_iter().rev() {
+        acc = apply_one(&args[0], vec![item, acc], env)?;
+    }
+    Ok(acc)
+}
+
+fn filter(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 2)?;
+    let mut kept = vec![];
+    for item in list_items(&args[1], "filter")? {
+        if apply_one(&args[0], vec![item.clone()], env)?.is_true() {
+            kept.push(item);
+        }
+    }
+    Ok(Value::list(kept))
+}
+
+fn is_number(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(Number::from_value(&args[0]).is_ok()))
+}
+
+/* whole floats like 2.0 count as integers too */
+fn is_integer(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(match args[0] {
+        Value::Integer(_) | Value::BigInt(_) => true,
+        Value::Float(n)                      => n.is_finite() && n.fract() == 0.0,
+        _                                    => false
+    }))
+}
+
+fn is_odd(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(odd(&args[0], "odd?")?))
+}
+
+fn is_even(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(!odd(&args[0], "even?")?))
+}
+
+fn odd(value: &Value, name: &str) -> Result<bool, EvalError> {
+    match *value {
+        Value::Integer(n)                                    => Ok(n % 2 != 0),
+        Value::BigInt(ref n)                                 => Ok(!n.div_rem(&BigInt::from_i64(2)).unwrap().1.is_zero()),
+        Value::Float(n) if n.is_finite() && n.fract() == 0.0 => Ok(n % 2.0 != 0.0),
+        ref other                                            => Err(EvalError::TypeError(format!("{}: not an integer: {:?}", name, other)))
+    }
+}
+
+fn is_string(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(matches!(args[0], Value::Str(_))))
+}
+
+fn is_symbol(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(matches!(args[0], Value::Symbol(_))))
+}
+
+fn is_procedure(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(matches!(args[0], Value::Procedure(_) | Value::Primitive(_) | Value::NativeProcedure(_) | Value::Continuation(_))))
+}
+
+fn is_boolean(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(matches!(args[0], Value::Bool(_))))
+}
+
+/* only #f is false, so (not 0) and (not '()) are #f */
+fn not(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(!args[0].is_true()))
+}
+
+fn string<'a>(value: &'a Value, name: &str) -> Result<&'a str, EvalError> {
+    match *value {
+        Value::Str(ref s) => Ok(s),
+        ref other         => Err(EvalError::TypeError(format!("{}: not a string: {:?}", name, other)))
+    }
+}
+
+/* an index into something of `length` items, `end` allows the index just past the last item */
+fn index(value: &Value, length: usize, end: bool, name: &str) -> Result<usize, EvalError> {
+    let index = match *value {
+        Value::Integer(n) => n,
+        ref other         => return Err(EvalError::TypeError(format!("{}: not an index: {:?}", name, other)))
+    };
+
+    let limit = if end { length } else { length.saturating_sub(1) };
+    if index < 0 || index as u64 > limit as u64 || (!end && length == 0) {
+        Err(EvalError::IndexOutOfBounds { index, length })
+    } else {
+        Ok(index as usize)
+    }
+}
+
+fn character(value: &Value, name: &str) -> Result<char, EvalError> {
+    match *value {
+        Value::Char(c) => Ok(c),
+        ref other      => Err(EvalError::TypeError(format!("{}: not a character: {:?}", name, other)))
+    }
+}
+
+fn is_char(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(matches!(args[0], Value::Char(_))))
+}
+
+fn char_to_integer(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Integer(i64::from(u32::from(character(&args[0], "char->integer")?))))
+}
+
+/* surrogates and anything past U+10FFFF aren't characters */
+fn integer_to_char(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    let code = match args[0] {
+        Value::Integer(n) => n,
+        ref other         => return Err(EvalError::TypeError(format!("integer->char: not an integer: {:?}", other)))
+    };
+    match u32::try_from(code).ok().and_then(char::from_u32) {
+        Some(c) => Ok(Value::Char(c)),
+        None    => Err(EvalError::TypeError(format!("integer->char: not a code point: {}", code)))
+    }
+}
+
+/* characters whose case changes to more than one character are left alone */
+fn char_upcase(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    let c = character(&args[0], "char-upcase")?;
+    let mut upper = c.to_uppercase();
+    Ok(Value::Char(if upper.len() == 1 { upper.next().unwrap() } else { c }))
+}
+
+fn char_downcase(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    let c = character(&args[0], "char-downcase")?;
+    let mut lower = c.to_lowercase();
+    Ok(Value::Char(if lower.len() == 1 { lower.next().unwrap() } else { c }))
+}
+
+fn char_eq(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    at_least(args, 1)?;
+    let chars = args.iter().map(|arg| character(arg, "char=?")).collect::<Result<Vec<_>, _>>()?;
+    Ok(Value::Bool(chars.windows(2).all(|pair| pair[0] == pair[1])))
+}
+
+/* lengths and indices count characters, not bytes */
+fn string_length(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Integer(string(&args[0], "string-length")?.chars().count() as i64))
+}
+
+fn string_append(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    let mut result = String::new();
+    for arg in args {
+        result.push_str(string(arg, "string-append")?);
+    }
+    Ok(Value::Str(result))
+}
+
+/* (substring s start [end]) */
+fn substring(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    at_least(args, 2)?;
+    if args.len() > 3 {
+        return Err(EvalError::ArityMismatch { expected: 3, got: args.len() })
+    }
+
+    let s      = string(&args[0], "substring")?;
+    let length = s.chars().count();
+    let start  = index(&args[1], length, true, "substring")?;
+    let end    = match args.get(2) {
+        Some(end) => index(end, length, true, "substring")?,
+        None      => length
+    };
+    if start > end {
+        return Err(EvalError::IndexOutOfBounds { index: start as i64, length: end })
+    }
+    Ok(Value::Str(s.chars().skip(start).take(end - start).collect()))
+}
+
+fn string_to_symbol(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Symbol(string(&args[0], "string->symbol")?.to_string()))
+}
+
+fn symbol_to_string(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    match args[0] {
+        Value::Symbol(ref s) => Ok(Value::Str(s.clone())),
+        ref other            => Err(EvalError::TypeError(format!("symbol->string: not a symbol: {:?}", other)))
+    }
+}
+
+fn string_eq(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    at_least(args, 1)?;
+    let strings = args.iter().map(|arg| string(arg, "string=?")).collect::<Result<Vec<_>, _>>()?;
+    Ok(Value::Bool(strings.windows(2).all(|pair| pair[0] == pair[1])))
+}
+
+fn vector(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    Ok(Value::vector(args.to_vec()))
+}
+
+/* (make-vector k [fill]), filled with #f unless given */
+fn make_vector(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    at_least(args, 1)?;
+    if args.len() > 2 {
+        return Err(EvalError::ArityMismatch { expected: 2, got: args.len() })
+    }
+
+    let length = match args[0] {
+        Value::Integer(n) if n >= 0 => n as usize,
+        ref other                   => return Err(EvalError::TypeError(format!("make-vector: not a length: {:?}", other)))
+    };
+    let fill = args.get(1).cloned().unwrap_or(Value::Bool(false));
+    Ok(Value::vector(vec![fill; length]))
+}
+
+fn is_vector(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(matches!(args[0], Value::Vector(_))))
+}
+
+fn vector_items<'a>(value: &'a Value, name: &str) -> Result<&'a RefCell<Vec<Value>>, EvalError> {
+    match *value {
+        Value::Vector(ref items) => Ok(items),
+        ref other                => Err(EvalError::TypeError(format!("{}: not a vector: {:?}", name, other)))
+    }
+}
+
+fn vector_length(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Integer(vector_items(&args[0], "vector-length")?.borrow().len() as i64))
+}
+
+fn vector_ref(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 2)?;
+    let items = vector_items(&args[0], "vector-ref")?.borrow();
+    let i     = index(&args[1], items.len(), false, "vector-ref")?;
+    Ok(items[i].clone())
+}
+
+fn vector_set(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 3)?;
+    let mut items = vector_items(&args[0], "vector-set!")?.borrow_mut();
+    let i         = index(&args[1], items.len(), false, "vector-set!")?;
+    items[i] = args[2].clone();
+    Ok(Value::Nil)
+}
+
+/* (call/cc proc) calls proc with an escape continuation: calling that while proc is still running
+   makes call/cc return its argument straight away */
+fn call_cc(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    let k = Rc::new(Continuation);
+    match apply(&args[0], vec![Value::Continuation(k.clone())], env) {
+        Err(EvalError::Escape(ref target, ref value)) if Rc::ptr_eq(target, &k) => Ok(value.clone()),
+        result                                                                  => result
+    }
+}
+
+fn values(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    Ok(Value::values(args.to_vec()))
+}
+
+/* (call-with-values producer consumer) calls consumer with the values producer returns */
+fn call_with_values(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 2)?;
+    let values = apply(&args[0], vec![], env)?.into_values();
+    apply(&args[1], values, env)
+}
+
+/* (error message irritant...) */
+fn error(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    at_least(args, 1)?;
+    let message = string(&args[0], "error")?.to_string();
+    Err(EvalError::UserError { message, irritants: args[1..].to_vec() })
+}
+
+fn condition<'a>(value: &'a Value, name: &str) -> Result<&'a EvalError, EvalError> {
+    match *value {
+        Value::Condition(ref err) => Ok(err),
+        ref other                 => Err(EvalError::TypeError(format!("{}: not an error object: {:?}", name, other)))
+    }
+}
+
+fn is_error_object(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    Ok(Value::Bool(matches!(args[0], Value::Condition(_))))
+}
+
+/* errors raised by the interpreter itself are described by their message alone */
+fn error_object_message(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    match *condition(&args[0], "error-object-message")? {
+        EvalError::UserError { ref message, .. } => Ok(Value::Str(message.clone())),
+        ref err                                  => Ok(Value::Str(err.to_string()))
+    }
+}
+
+fn error_object_irritants(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    match *condition(&args[0], "error-object-irritants")? {
+        EvalError::UserError { ref irritants, .. } => Ok(Value::list(irritants.clone())),
+        _                                          => Ok(Value::Nil)
+    }
+}
+
+/* (with-exception-handler handler thunk) calls thunk, and if it fails returns what handler
+   gives for the error instead */
+fn with_exception_handler(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 2)?;
+    match apply(&args[1], vec![], env) {
+        Ok(value)                        => Ok(value),
+        Err(err @ EvalError::Escape(..)) => Err(err),
+        Err(err)                         => apply(&args[0], vec![Value::Condition(Rc::new(err))], env)
+    }
+}
+
+/* pairs are the same only if they are the same allocation, other values compare shallowly.
+   strings are stored by value so they compare by contents */
+fn is_eqv(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 2)?;
+    let same = match (&args[0], &args[1]) {
+        (Value::Pair(a), Value::Pair(b))     => Rc::ptr_eq(a, b),
+        (Value::Vector(a), Value::Vector(b)) => Rc::ptr_eq(a, b),
+        (a, b)                               => a == b
+    };
+    Ok(Value::Bool(same))
+}
+
+/* structural equality, recursing into pairs and vectors */
+fn is_equal(args: &[Value], _: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 2)?;
+    Ok(Value::Bool(args[0] == args[1]))
+}
+
+/* (display x) prints x without quoting strings */
+fn display(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    output(env, &args[0].display().to_string())
+}
+
+/* (write x) prints x so that it could be read back in */
+fn write(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 1)?;
+    output(env, &args[0].write().to_string())
+}
+
+fn newline(args: &[Value], env: &mut Environment) -> Result<Value, EvalError> {
+    exactly(args, 0)?;
+    output(env, "\n")
+}
+
+fn output(env: &Environment, text: &str) -> Result<Value, EvalError> {
+    match env.write_output(text) {
+        Ok(())   => Ok(Value::Nil),
+        Err(err) => Err(EvalError::Io(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eval::eval;
+    use eval::Environment;
+    use eval::EvalError;
+    use eval::Value;
+    use parser::read_str;
+    use std::cell::RefCell;
+    use std::io;
+    use std::io::Write;
+    use std::rc::Rc;
+
+    fn run(source: &str) -> Result<Value, EvalError> {
+        eval(&read_str(source).unwrap(), &mut Environment::global())
+    }
+
+    /* a Write sink the test can still read after handing a clone to the environment */
+    #[derive(Clone)]
+    struct Capture(Rc<RefCell<Vec<u8>>>);
+
+    impl Capture {
+        fn new() -> Capture {
+            Capture(Rc::new(RefCell::new(vec![])))
+        }
+
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+
+    /* evaluate `source`, returning what it printed */
+    fn output(source: &str) -> String {
+        let buffer  = Capture::new();
+        let mut env = Environment::global();
+        env.set_output(buffer.clone());
+        eval(&read_str(source).unwrap(), &mut env).unwrap();
+        buffer.contents()
+    }
+
+    #[test]
+    fn add() {
+        assert_eq!(run("(+ 1 2 3)"), Ok(Value::Integer(6)));
+        assert_eq!(run("(+)"), Ok(Value::Integer(0)));
+        assert_eq!(run("(+ 1 2.0)"), Ok(Value::Float(3.0)));
+    }
+
+    #[test]
+    fn sub() {
+        assert_eq!(run("(- 10 1 2)"), Ok(Value::Integer(7)));
+        assert_eq!(run("(- 5)"), Ok(Value::Integer(-5)));
+        assert_eq!(run("(- 1.5 1)"), Ok(Value::Float(0.5)));
+        assert_eq!(run("(-)"), Err(EvalError::ArityMismatch { expected: 1, got: 0 }));
+    }
+
+    #[test]
+    fn mul() {
+        assert_eq!(run("(* 2 3 4)"), Ok(Value::Integer(24)));
+        assert_eq!(run("(*)"), Ok(Value::Integer(1)));
+        assert_eq!(run("(* 2 0.5)"), Ok(Value::Float(1.0)));
+    }
+
+    #[test]
+    fn div() {
+        assert_eq!(run("(/ 12 2 3)"), Ok(Value::Integer(2)));
+        assert_eq!(run("(/ 6 3)"), Ok(Value::Integer(2)));
+        assert_eq!(run("(/ 1 2)").unwrap().to_string(), "1/2");
+        assert_eq!(run("(/ 4)").unwrap().to_string(), "1/4");
+        assert_eq!(run("(/ 3.0 2)"), Ok(Value::Float(1.5)));
+    }
+
+    #[test]
+    fn rational() {
+        assert_eq!(run("(/ 1 3)").unwrap().to_string(), "1/3");
+        assert_eq!(run("(+ 1/2 1/3)").unwrap().to_string(), "5/6");
+        assert_eq!(run("(* 2/3 3/2)"), Ok(Value::Integer(1)));
+        assert_eq!(run("(- 1/2)").unwrap().to_string(), "-1/2");
+        assert_eq!(run("(+ 1/2 0.5)"), Ok(Value::Float(1.0)));
+        assert_eq!(run("(< 1/3 0.5 2/3 1)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(= 1/2 2/4)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(equal? (/ 1 2) 1/2)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(/ 1/2 0)"), Err(EvalError::DivideByZero));
+    }
+
+    #[test]
+    fn divide_by_zero() {
+        assert_eq!(run("(/ 1 0)"), Err(EvalError::DivideByZero));
+        assert_eq!(run("(/ 0)"), Err(EvalError::DivideByZero));
+    }
+
+    #[test]
+    fn nested() {
+        assert_eq!(run("(* (+ 1 2) (- 10 4))"), Ok(Value::Integer(18)));
+    }
+
+    #[test]
+    fn non_numeric() {
+        match run("(+ 1 \"a\")") {
+            Err(EvalError::TypeError(_)) => (),
+            other                        => panic!("expected a type error, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(run("(< 1 2 3)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(< 1 3 2)"), Ok(Value::Bool(false)));
+        assert_eq!(run("(> 3 2 1)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(<= 1 1 2)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(>= 2 2 3)"), Ok(Value::Bool(false)));
+        assert_eq!(run("(= 1 1 1)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(= 1 1 2)"), Ok(Value::Bool(false)));
+        assert_eq!(run("(< 1)"), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn comparisons_promote() {
+        assert_eq!(run("(= 2 2.0)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(< 1 1.5 2)"), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn comparison_type_error() {
+        match run("(< 1 \"a\")") {
+            Err(EvalError::TypeError(_)) => (),
+            other                        => panic!("expected a type error, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn comparison_in_condition() {
+        assert_eq!(run("(if (< 1 2) \"yes\" \"no\")"), Ok(Value::Str("yes".to_string())));
+    }
+
+    fn int_list(values: &[i64]) -> Value {
+        Value::list(values.iter().map(|&n| Value::Integer(n)).collect())
+    }
+
+    #[test]
+    fn cons_car_cdr() {
+        assert_eq!(run("(car (cons 1 2))"), Ok(Value::Integer(1)));
+        assert_eq!(run("(cdr (cons 1 2))"), Ok(Value::Integer(2)));
+        assert_eq!(run("(cons 1 '())"), Ok(int_list(&[1])));
+    }
+
+    #[test]
+    fn list() {
+        assert_eq!(run("(list 1 2 3)"), Ok(int_list(&[1, 2, 3])));
+        assert_eq!(run("(list)"), Ok(Value::Nil));
+        assert_eq!(run("(car (cdr (list 1 2 3)))"), Ok(Value::Integer(2)));
+        assert_eq!(run("(cdr (cdr (cdr (list 1 2 3))))"), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn predicates() {
+        assert_eq!(run("(null? '())"), Ok(Value::Bool(true)));
+        assert_eq!(run("(null? (list 1))"), Ok(Value::Bool(false)));
+        assert_eq!(run("(pair? (cons 1 2))"), Ok(Value::Bool(true)));
+        assert_eq!(run("(pair? '())"), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn car_of_empty() {
+        match run("(car '())") {
+            Err(EvalError::TypeError(_)) => (),
+            other                        => panic!("expected a type error, got {:?}", other)
+        }
+        match run("(cdr 5)") {
+            Err(EvalError::TypeError(_)) => (),
+            other                        => panic!("expected a type error, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn set_car_and_cdr() {
+        assert_eq!(run("(begin (define p (cons 1 2)) (set-car! p 99) (car p))"), Ok(Value::Integer(99)));
+        assert_eq!(run("(begin (define p (cons 1 2)) (set-cdr! p '(3)) p)").unwrap().to_string(), "(1 3)");
+
+        let source = "(let* ((p (list 1 2 3)) (q p) (tail (cdr p))) \
+                        (set-car! q 'one) \
+                        (set-car! tail 'two) \
+                        (list p q (eq? p q)))";
+        assert_eq!(run(source).unwrap().to_string(), "((one two 3) (one two 3) #t)");
+        let source = "(let ((p (list 1 2))) (set-cdr! (cdr p) p) (map car p))";
+        assert_eq!(run(source), Err(EvalError::TypeError("map: not a list: Pair((1 2 ...))".to_string())));
+        assert_eq!(run("(set-car! '() 1)"), Err(EvalError::TypeError("set-car!: not a pair: Nil".to_string())));
+    }
+
+    #[test]
+    fn list_arity() {
+        assert_eq!(run("(cons 1)"), Err(EvalError::ArityMismatch { expected: 2, got: 1 }));
+        assert_eq!(run("(car)"), Err(EvalError::ArityMismatch { expected: 1, got: 0 }));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(output("(begin (display \"hi\") (newline))"), "hi\n");
+        assert_eq!(output("(display '(1 \"a\" 2.5))"), "(1 a 2.5)");
+    }
+
+    #[test]
+    fn write() {
+        assert_eq!(output("(write \"hi\")"), "\"hi\"");
+        assert_eq!(output("(write '(a \"b\\\"c\"))"), "(a \"b\\\"c\")");
+    }
+
+    #[test]
+    fn output_from_nested_scope() {
+        assert_eq!(output("((lambda (x) (let ((y 2)) (display x) (display y))) 1)"), "12");
+    }
+
+    #[test]
+    fn quote_list() {
+        assert_eq!(run("'(1 2)"), Ok(int_list(&[1, 2])));
+        assert_eq!(run("(quote (a . b))"), Ok(Value::cons(Value::Symbol("a".to_string()), Value::Symbol("b".to_string()))));
+    }
+
+    #[test]
+    fn equal() {
+        assert_eq!(run("(equal? '(1 2) '(1 2))"), Ok(Value::Bool(true)));
+        assert_eq!(run("(equal? '(1 (2 \"x\")) (list 1 (list 2 \"x\")))"), Ok(Value::Bool(true)));
+        assert_eq!(run("(equal? '(1 2) '(1 2 3))"), Ok(Value::Bool(false)));
+        assert_eq!(run("(equal? 1 1.0)"), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn eq() {
+        assert_eq!(run("(eq? '() '())"), Ok(Value::Bool(true)));
+        assert_eq!(run("(eq? 'a 'a)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(eqv? 2 2)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(eqv? 2 2.0)"), Ok(Value::Bool(false)));
+        assert_eq!(run("(eq? '(1 2) '(1 2))"), Ok(Value::Bool(false)));
+        assert_eq!(run("(let ((x '(1 2))) (eq? x x))"), Ok(Value::Bool(true)));
+        assert_eq!(run("(eq? car car)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(eq? 1)"), Err(EvalError::ArityMismatch { expected: 2, got: 1 }));
+    }
+
+    #[test]
+    fn apply() {
+        assert_eq!(run("(apply + '(1 2 3))"), Ok(Value::Integer(6)));
+        assert_eq!(run("(apply list 1 2 '(3 4))"), Ok(int_list(&[1, 2, 3, 4])));
+        assert_eq!(run("(apply (lambda args args) '())"), Ok(Value::Nil));
+        assert!(run("(apply + 1)").is_err());
+        assert_eq!(run("(apply +)"), Err(EvalError::ArityMismatch { expected: 2, got: 1 }));
+    }
+
+    #[test]
+    fn map() {
+        assert_eq!(run("(map (lambda (x) (* x x)) '(1 2 3))"), Ok(int_list(&[1, 4, 9])));
+        assert_eq!(run("(map + '(1 2) '(10 20))"), Ok(int_list(&[11, 22])));
+        assert_eq!(run("(map + '(1 2 3) '(10 20))"), Ok(int_list(&[11, 22])));
+        assert_eq!(run("(map car '())"), Ok(Value::Nil));
+        assert!(run("(map car '(1))").is_err());
+    }
+
+    #[test]
+    fn for_each() {
+        let printed = output("(for-each (lambda (x y) (display (+ x y))) '(1 2) '(3 4 5))");
+        assert_eq!(printed, "46");
+        assert_eq!(run("(for-each car '())"), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn bignum_factorial() {
+        let factorial = run("(* 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25)").unwrap();
+        assert_eq!(factorial.to_string(), "15511210043330985984000000");
+        assert_eq!(run("(= (/ (* 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25) 25) \
+                          (* 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24))"), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn bignum_literal() {
+        let big = run("99999999999999999999").unwrap();
+        assert_eq!(big.to_string(), "99999999999999999999");
+        assert_eq!(run("(> 99999999999999999999 9223372036854775807)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(- 9223372036854775808 1)"), Ok(Value::Integer(i64::MAX)));
+        assert_eq!(run("(+ 9223372036854775807 1)").unwrap().to_string(), "9223372036854775808");
+        assert_eq!(run("(- -9223372036854775808)").unwrap().to_string(), "9223372036854775808");
+    }
+
+    fn string(s: &str) -> Value {
+        Value::Str(s.to_string())
+    }
+
+    #[test]
+    fn string_length() {
+        assert_eq!(run("(string-length \"hello\")"), Ok(Value::Integer(5)));
+        assert_eq!(run("(string-length \"café\")"), Ok(Value::Integer(4)));
+        assert_eq!(run("(string-length \"\")"), Ok(Value::Integer(0)));
+        match run("(string-length 5)") {
+            Err(EvalError::TypeError(_)) => (),
+            other                        => panic!("expected a type error, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn string_append() {
+        assert_eq!(run("(string-append \"ab\" \"cd\")"), Ok(string("abcd")));
+        assert_eq!(run("(string-append)"), Ok(string("")));
+        assert!(run("(string-append \"a\" 'b)").is_err());
+    }
+
+    #[test]
+    fn substring() {
+        assert_eq!(run("(substring \"hello\" 1 3)"), Ok(string("el")));
+        assert_eq!(run("(substring \"hello\" 2)"), Ok(string("llo")));
+        assert_eq!(run("(substring \"naïve café\" 2 7)"), Ok(string("ïve c")));
+        assert_eq!(run("(substring \"hello\" 5 5)"), Ok(string("")));
+        assert_eq!(run("(substring \"hello\" 2 6)"), Err(EvalError::IndexOutOfBounds { index: 6, length: 5 }));
+        assert_eq!(run("(substring \"hello\" -1 2)"), Err(EvalError::IndexOutOfBounds { index: -1, length: 5 }));
+        assert!(run("(substring \"hello\" 3 2)").is_err());
+    }
+
+    #[test]
+    fn symbols_and_strings() {
+        assert_eq!(run("(string->symbol \"abc\")"), Ok(Value::Symbol("abc".to_string())));
+        assert_eq!(run("(symbol->string 'abc)"), Ok(string("abc")));
+        assert_eq!(run("(eq? (string->symbol \"x\") 'x)"), Ok(Value::Bool(true)));
+        assert!(run("(symbol->string \"abc\")").is_err());
+    }
+
+    #[test]
+    fn string_eq() {
+        assert_eq!(run("(string=? \"café\" \"café\" \"café\")"), Ok(Value::Bool(true)));
+        assert_eq!(run("(string=? \"a\" \"b\")"), Ok(Value::Bool(false)));
+        assert!(run("(string=? \"a\" 'a)").is_err());
+    }
+
+    #[test]
+    fn type_predicates() {
+        assert_eq!(run("(number? 1.0)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(number? 1/2)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(number? \"1\")"), Ok(Value::Bool(false)));
+        assert_eq!(run("(integer? 3)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(integer? 2.0)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(integer? 2.5)"), Ok(Value::Bool(false)));
+        assert_eq!(run("(integer? 1/2)"), Ok(Value::Bool(false)));
+        assert_eq!(run("(integer? 'a)"), Ok(Value::Bool(false)));
+        assert_eq!(run("(string? \"x\")"), Ok(Value::Bool(true)));
+        assert_eq!(run("(string? 'x)"), Ok(Value::Bool(false)));
+        assert_eq!(run("(symbol? 'x)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(symbol? \"x\")"), Ok(Value::Bool(false)));
+        assert_eq!(run("(procedure? car)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(procedure? (lambda (x) x))"), Ok(Value::Bool(true)));
+        assert_eq!(run("(procedure? 'car)"), Ok(Value::Bool(false)));
+        assert_eq!(run("(boolean? #f)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(boolean? '())"), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn not() {
+        assert_eq!(run("(not #f)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(not 0)"), Ok(Value::Bool(false)));
+        assert_eq!(run("(not '())"), Ok(Value::Bool(false)));
+        assert_eq!(run("(not)"), Err(EvalError::ArityMismatch { expected: 1, got: 0 }));
+    }
+
+    #[test]
+    fn error() {
+        let expected = EvalError::UserError { message: "boom".to_string(), irritants: vec![Value::Integer(1), Value::Integer(2)] };
+        assert_eq!(run("(error \"boom\" 1 2)"), Err(expected));
+        assert_eq!(run("(error \"boom\" 1 \"two\")").unwrap_err().to_string(), "boom 1 \"two\"");
+        assert!(run("(error 'boom)").is_err());
+    }
+
+    #[test]
+    fn with_exception_handler() {
+        let source = "(with-exception-handler (lambda (e) (error-object-message e)) (lambda () (error \"boom\")))";
+        assert_eq!(run(source), Ok(Value::Str("boom".to_string())));
+        assert_eq!(run("(with-exception-handler (lambda (e) 0) (lambda () 1))"), Ok(Value::Integer(1)));
+        assert_eq!(run("(with-exception-handler error-object? (lambda () (car 1)))"), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn vectors() {
+        assert_eq!(run("(vector-ref (vector 10 20 30) 1)"), Ok(Value::Integer(20)));
+        assert_eq!(run("(vector-length (make-vector 3 0))"), Ok(Value::Integer(3)));
+        assert_eq!(run("(make-vector 2 'x)"), Ok(Value::vector(vec![Value::Symbol("x".to_string()); 2])));
+        assert_eq!(run("(vector-ref #(1 (2)) 1)"), Ok(int_list(&[2])));
+        assert_eq!(run("(list (vector? #()) (vector? '(1)))"), Ok(Value::list(vec![Value::Bool(true), Value::Bool(false)])));
+        assert_eq!(run("(vector 1 \"a\" #(b))").unwrap().to_string(), "#(1 \"a\" #(b))");
+        assert!(run("(make-vector -1)").is_err());
+    }
+
+    #[test]
+    fn vector_set() {
+        assert_eq!(run("((lambda (v) (vector-set! v 0 'a) (vector-set! v 2 'c) v) (make-vector 3))").unwrap().to_string(), "#(a #f c)");
+
+        let source = "(let* ((v (vector 1 2)) (w v)) \
+                        (vector-set! w 1 'two) \
+                        (list v (eq? v w) (eq? v (vector 1 'two)) (equal? v (vector 1 'two))))";
+        assert_eq!(run(source).unwrap().to_string(), "(#(1 two) #t #f #t)");
+    }
+
+    #[test]
+    fn vector_out_of_bounds() {
+        assert_eq!(run("(vector-ref (vector 10 20 30) 3)"), Err(EvalError::IndexOutOfBounds { index: 3, length: 3 }));
+        assert_eq!(run("(vector-ref (vector) 0)"), Err(EvalError::IndexOutOfBounds { index: 0, length: 0 }));
+        assert_eq!(run("(vector-set! (vector 1) -1 0)"), Err(EvalError::IndexOutOfBounds { index: -1, length: 1 }));
+        assert!(run("(vector-ref '(1 2) 0)").is_err());
+    }
+
+    #[test]
+    fn call_cc() {
+        assert_eq!(run("(call/cc (lambda (k) (+ 1 (k 42))))"), Ok(Value::Integer(42)));
+        assert_eq!(run("(call/cc (lambda (k) (+ 1 2)))"), Ok(Value::Integer(3)));
+        assert_eq!(run("(+ 1 (call-with-current-continuation (lambda (k) (k 1) 5)))"), Ok(Value::Integer(2)));
+        assert_eq!(run("(call/cc procedure?)"), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn call_cc_early_return() {
+        let source = "(let ((found (lambda (x lst) \
+                                 (call/cc (lambda (return) \
+                                   (for-each (lambda (y) (if (equal? x y) (return #t))) lst) \
+                                   #f))))) \
+                        (list (found 2 '(1 2 3)) (found 4 '(1 2 3))))";
+        assert_eq!(run(source).unwrap().to_string(), "(#t #f)");
+    }
+
+    #[test]
+    fn call_cc_nested() {
+        let source = "(call/cc (lambda (outer) (+ 1 (call/cc (lambda (inner) (outer 10))))))";
+        assert_eq!(run(source), Ok(Value::Integer(10)));
+        let source = "(call/cc (lambda (outer) (+ 1 (call/cc (lambda (inner) (inner 10))))))";
+        assert_eq!(run(source), Ok(Value::Integer(11)));
+    }
+
+    #[test]
+    fn call_cc_escapes_handlers() {
+        assert_eq!(run("(call/cc (lambda (k) (guard (e (#t 'caught)) (k 'escaped))))"), Ok(Value::Symbol("escaped".to_string())));
+        let source = "(call/cc (lambda (k) (with-exception-handler (lambda (e) 'caught) (lambda () (k 'escaped)))))";
+        assert_eq!(run(source), Ok(Value::Symbol("escaped".to_string())));
+    }
+
+    #[test]
+    fn call_with_values() {
+        assert_eq!(run("(call-with-values (lambda () (values 1 2)) +)"), Ok(Value::Integer(3)));
+        assert_eq!(run("(call-with-values (lambda () 5) list)"), Ok(Value::list(vec![Value::Integer(5)])));
+        assert_eq!(run("(call-with-values values list)"), Ok(Value::Nil));
+        assert_eq!(run("(values 7)"), Ok(Value::Integer(7)));
+    }
+
+    #[test]
+    fn values_in_single_value_context() {
+        assert_eq!(run("(+ 1 (values 2 3))"), Err(EvalError::TypeError("expected one value, got 2".to_string())));
+        assert_eq!(run("(if (values) 1 2)"), Err(EvalError::TypeError("expected one value, got 0".to_string())));
+        assert_eq!(run("(map (lambda (x) (values x x)) '(1 2))"), Err(EvalError::TypeError("expected one value, got 2".to_string())));
+        assert_eq!(run("(filter (lambda (x) (values)) '(1 2))"), Err(EvalError::TypeError("expected one value, got 0".to_string())));
+        assert_eq!(run("(fold-left (lambda (acc x) (values acc x)) 0 '(1))"), Err(EvalError::TypeError("expected one value, got 2".to_string())));
+    }
+
+    #[test]
+    fn call_cc_after_return() {
+        let source = "(let ((k (call/cc (lambda (k) k)))) (k 1))";
+        assert_eq!(run(source).unwrap_err().to_string(), "continuation called after its call/cc returned");
+        assert_eq!(run("(call/cc (lambda (k) (k 1 2)))"), Err(EvalError::ArityMismatch { expected: 1, got: 2 }));
+    }
+
+    #[test]
+    fn fold_left() {
+        assert_eq!(run("(fold-left + 0 '(1 2 3))"), Ok(Value::Integer(6)));
+        assert_eq!(run("(fold-left cons '() '(1 2))").unwrap().to_string(), "((() . 1) . 2)");
+        assert_eq!(run("(fold-left + 7 '())"), Ok(Value::Integer(7)));
+        assert!(run("(fold-left + 0 5)").is_err());
+    }
+
+    #[test]
+    fn fold_right() {
+        assert_eq!(run("(fold-right cons '() '(1 2 3))"), Ok(int_list(&[1, 2, 3])));
+        assert_eq!(run("(fold-right list 'end '(1 2))").unwrap().to_string(), "(1 (2 end))");
+        assert_eq!(run("(fold-right - 0 '(1 2 3))"), Ok(Value::Integer(2)));
+    }
+
+    #[test]
+    fn filter() {
+        assert_eq!(run("(filter odd? '(1 2 3 4))"), Ok(int_list(&[1, 3])));
+        assert_eq!(run("(filter (lambda (x) x) '(1 #f 2))"), Ok(int_list(&[1, 2])));
+        assert_eq!(run("(filter even? '())"), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn odd_even() {
+        assert_eq!(run("(map odd? '(-3 0 7 4.0))").unwrap().to_string(), "(#t #f #t #f)");
+        assert_eq!(run("(even? 100000000000000000001)"), Ok(Value::Bool(false)));
+        assert!(run("(odd? 1.5)").is_err());
+    }
+
+    #[test]
+    fn chars() {
+        assert_eq!(run("(char->integer #\\A)"), Ok(Value::Integer(65)));
+        assert_eq!(run("(integer->char 97)"), Ok(Value::Char('a')));
+        assert_eq!(run("(integer->char 955)").unwrap().to_string(), "#\\λ");
+        assert_eq!(run("(char-upcase #\\a)"), Ok(Value::Char('A')));
+        assert_eq!(run("(char-downcase #\\Λ)"), Ok(Value::Char('λ')));
+        assert_eq!(run("(char-upcase #\\ß)"), Ok(Value::Char('ß')));
+        assert_eq!(run("(char=? #\\a #\\a)"), Ok(Value::Bool(true)));
+        assert_eq!(run("(char=? #\\a #\\a #\\b)"), Ok(Value::Bool(false)));
+        assert_eq!(run("(list (char? #\\a) (char? \"a\"))").unwrap().to_string(), "(#t #f)");
+    }
+
+    #[test]
+    fn char_errors() {
+        assert!(matches!(run("(integer->char 55296)"), Err(EvalError::TypeError(_))));
+        assert!(matches!(run("(integer->char -1)"), Err(EvalError::TypeError(_))));
+        assert!(matches!(run("(integer->char 1114112)"), Err(EvalError::TypeError(_))));
+        assert!(matches!(run("(char->integer \"a\")"), Err(EvalError::TypeError(_))));
+        assert!(run("(char=? #\\a 1)").is_err());
+    }
+
+    #[test]
+    fn display_write_chars() {
+        assert_eq!(output("(begin (display #\\a) (write #\\a) (write #\\space) (display (list #\\b)))"), "a#\\a#\\space(b)");
+    }
+}
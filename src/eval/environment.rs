@@ -0,0 +1,231 @@
+use eval::EvalError;
+use eval::Native;
+use eval::Primitive;
+use eval::PrimitiveFn;
+use eval::Value;
+use eval::builtins;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::rc::Rc;
+
+/* a handle to a scope; clones share the same bindings so closures can capture it */
+#[derive(Clone)]
+pub struct Environment {
+    frame: Rc<RefCell<Frame>>
+}
+
+struct Frame {
+    bindings: HashMap<String, Value>,
+    parent:   Option<Environment>,
+    output:   Option<Box<dyn Write>>
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment::with_parent(None)
+    }
+
+    /* a root scope with all the builtin procedures defined */
+    pub fn global() -> Environment {
+        let mut env = Environment::new();
+        builtins::define_builtins(&mut env);
+        env
+    }
+
+    /* a new empty scope whose lookups fall through to `self` */
+    pub fn child(&self) -> Environment {
+        Environment::with_parent(Some(self.clone()))
+    }
+
+    fn with_parent(parent: Option<Environment>) -> Environment {
+        let frame = Frame { bindings: HashMap::new(), parent, output: None };
+        Environment { frame: Rc::new(RefCell::new(frame)) }
+    }
+
+    /* bind `name` in this scope, shadowing any outer binding */
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.frame.borrow_mut().bindings.insert(name.to_string(), value);
+    }
+
+    pub fn define_primitive(&mut self, name: &'static str, func: PrimitiveFn) {
+        self.define(name, Value::Primitive(Primitive { name, func }));
+    }
+
+    /* expose a host function to Scheme code, callable like any other procedure */
+    pub fn define_native<F>(&mut self, name: &str, func: F)
+        where F: Fn(&[Value]) -> Result<Value, EvalError> + 'static {
+        self.define(name, Value::NativeProcedure(Native { name: name.to_string(), func: Rc::new(func) }));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        let frame = self.frame.borrow();
+        match frame.bindings.get(name) {
+            Some(value) => Some(value.clone()),
+            None        => frame.parent.as_ref().and_then(|parent| parent.get(name))
+        }
+    }
+
+    /* rebind the nearest existing `name`, returns false if it isn't bound anywhere */
+    pub fn set(&mut self, name: &str, value: Value) -> bool {
+        let mut frame = self.frame.borrow_mut();
+        if let Some(binding) = frame.bindings.get_mut(name) {
+            *binding = value;
+            return true
+        }
+
+        match frame.parent {
+            Some(ref mut parent) => parent.set(name, value),
+            None                 => false
+        }
+    }
+}
+
+impl Environment {
+    /* send everything printed through this environment to `output` instead of stdout,
+       the sink lives in the root scope so it is shared by every scope in the program */
+    pub fn set_output<W: Write + 'static>(&mut self, output: W) {
+        self.root().frame.borrow_mut().output = Some(Box::new(output));
+    }
+
+    pub fn write_output(&self, text: &str) -> io::Result<()> {
+        let root      = self.root();
+        let mut frame = root.frame.borrow_mut();
+        match frame.output {
+            Some(ref mut output) => {
+                output.write_all(text.as_bytes())?;
+                output.flush()
+            },
+            None                 => {
+                let stdout     = io::stdout();
+                let mut handle = stdout.lock();
+                handle.write_all(text.as_bytes())?;
+                handle.flush()
+            }
+        }
+    }
+
+    fn root(&self) -> Environment {
+        let mut env = self.clone();
+        loop {
+            let parent = env.frame.borrow().parent.clone();
+            match parent {
+                Some(parent) => env = parent,
+                None         => return env
+            }
+        }
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Environment {
+        Environment::new()
+    }
+}
+
+impl fmt::Debug for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Environment")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eval::eval;
+    use parser::read_str;
+    use std::cell::Cell;
+
+    #[test]
+    fn define_get() {
+        let mut env = Environment::new();
+        env.define("x", Value::Integer(1));
+        assert_eq!(env.get("x"), Some(Value::Integer(1)));
+        assert_eq!(env.get("y"), None);
+    }
+
+    #[test]
+    fn define_overwrites() {
+        let mut env = Environment::new();
+        env.define("x", Value::Integer(1));
+        env.define("x", Value::Bool(true));
+        assert_eq!(env.get("x"), Some(Value::Bool(true)));
+    }
+
+    #[test]
+    fn child_falls_through() {
+        let mut parent = Environment::new();
+        parent.define("x", Value::Integer(1));
+        let child = parent.child();
+        assert_eq!(child.get("x"), Some(Value::Integer(1)));
+
+        parent.define("y", Value::Integer(2));
+        assert_eq!(child.get("y"), Some(Value::Integer(2)));
+    }
+
+    #[test]
+    fn child_shadows() {
+        let mut parent = Environment::new();
+        parent.define("x", Value::Integer(1));
+        let mut child = parent.child();
+        child.define("x", Value::Integer(2));
+        assert_eq!(child.get("x"), Some(Value::Integer(2)));
+        assert_eq!(parent.get("x"), Some(Value::Integer(1)));
+    }
+
+    #[test]
+    fn set_updates_nearest() {
+        let mut parent = Environment::new();
+        parent.define("x", Value::Integer(1));
+        let mut child = parent.child();
+        assert!(child.set("x", Value::Integer(2)));
+        assert_eq!(parent.get("x"), Some(Value::Integer(2)));
+    }
+
+    #[test]
+    fn global_has_builtins() {
+        match Environment::global().get("+") {
+            Some(Value::Primitive(primitive)) => assert_eq!(primitive.name, "+"),
+            other                             => panic!("expected a primitive, got {:?}", other)
+        }
+        assert_eq!(Environment::new().get("+"), None);
+    }
+
+    #[test]
+    fn native_procedure() {
+        let mut env = Environment::global();
+        env.define_native("double", |args| match args {
+            [Value::Integer(n)] => Ok(Value::Integer(2 * n)),
+            _                   => Err(EvalError::TypeError("double: expected one integer".to_string()))
+        });
+
+        assert_eq!(eval(&read_str("(double 21)").unwrap(), &mut env), Ok(Value::Integer(42)));
+        assert_eq!(eval(&read_str("(double (double 1))").unwrap(), &mut env), Ok(Value::Integer(4)));
+        assert!(eval(&read_str("(double \"x\")").unwrap(), &mut env).is_err());
+    }
+
+    #[test]
+    fn native_procedure_captures_host_state() {
+        let calls   = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let mut env = Environment::new();
+        env.define_native("tick", move |_| {
+            counter.set(counter.get() + 1);
+            Ok(Value::Integer(counter.get()))
+        });
+
+        eval(&read_str("(tick)").unwrap(), &mut env).unwrap();
+        assert_eq!(eval(&read_str("(tick)").unwrap(), &mut env), Ok(Value::Integer(2)));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn set_unbound() {
+        let mut env = Environment::new();
+        assert!(!env.set("x", Value::Integer(1)));
+        assert_eq!(env.get("x"), None);
+    }
+}
@@ -0,0 +1,1135 @@
+use eval::Continuation;
+use eval::Environment;
+use eval::Lambda;
+use eval::Macro;
+use eval::Value;
+use parser::Sexpr;
+
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum EvalError {
+    Unbound(String),
+    BadSyntax(String),
+    ArityMismatch { expected: usize, got: usize },
+    TypeError(String),
+    DivideByZero,
+    IndexOutOfBounds { index: i64, length: usize },
+    Io(String),
+    UserError { message: String, irritants: Vec<Value> },
+    Escape(Rc<Continuation>, Value)
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EvalError::Unbound(ref name)      => write!(f, "unbound variable: {}", name),
+            EvalError::BadSyntax(ref message) => write!(f, "bad syntax: {}", message),
+            EvalError::ArityMismatch { expected, got } => {
+                write!(f, "wrong number of arguments: expected {}, got {}", expected, got)
+            },
+            EvalError::TypeError(ref message) => write!(f, "type error: {}", message),
+            EvalError::DivideByZero           => write!(f, "division by zero"),
+            EvalError::IndexOutOfBounds { index, length } => {
+                write!(f, "index {} out of bounds for length {}", index, length)
+            },
+            EvalError::Io(ref message)        => write!(f, "i/o error: {}", message),
+            EvalError::UserError { ref message, ref irritants } => {
+                write!(f, "{}", message)?;
+                for irritant in irritants {
+                    write!(f, " {}", irritant)?;
+                }
+                Ok(())
+            },
+            EvalError::Escape(..)             => write!(f, "continuation called after its call/cc returned")
+        }
+    }
+}
+
+impl Error for EvalError {}
+
+/* how far evaluating an expression got: either a value, or a call left in tail position
+   for the trampoline in `run` to make without growing the Rust stack */
+enum Step {
+    Done(Value),
+    TailCall(Value, Vec<Value>)
+}
+
+/* tail positions, evaluated with eval_step rather than eval:
+     - the chosen branch of `if`
+     - the last expression of `begin`, of a taken `when` or `unless` body, of a `cond` clause body, and of a `let`, `let*`, `letrec` or procedure body
+     - the last operand of `and` and `or`
+     - the expansion of a macro use */
+pub fn eval(expr: &Sexpr, env: &mut Environment) -> Result<Value, EvalError> {
+    let step = eval_step(expr, env)?;
+    run(step, env)
+}
+
+/* call `procedure` with already evaluated arguments, `env` is the caller's environment */
+pub fn apply(procedure: &Value, args: Vec<Value>, env: &mut Environment) -> Result<Value, EvalError> {
+    let step = call(procedure, args, env)?;
+    run(step, env)
+}
+
+/* call `procedure` for a value that's used directly, e.g. by a builtin like map, see eval_one */
+pub fn apply_one(procedure: &Value, args: Vec<Value>, env: &mut Environment) -> Result<Value, EvalError> {
+    apply(procedure, args, env).and_then(single)
+}
+
+/* evaluate an expression whose value is used directly, e.g. an argument or a test, where
+   multiple values from `values` can't go */
+fn eval_one(expr: &Sexpr, env: &mut Environment) -> Result<Value, EvalError> {
+    eval(expr, env).and_then(single)
+}
+
+fn single(value: Value) -> Result<Value, EvalError> {
+    match value {
+        Value::Values(values) => Err(EvalError::TypeError(format!("expected one value, got {}", values.len()))),
+        value                 => Ok(value)
+    }
+}
+
+/* keep making tail calls until one produces a value */
+fn run(mut step: Step, env: &mut Environment) -> Result<Value, EvalError> {
+    loop {
+        match step {
+            Step::Done(value)               => return Ok(value),
+            Step::TailCall(procedure, args) => step = call(&procedure, args, env)?
+        }
+    }
+}
+
+fn eval_step(expr: &Sexpr, env: &mut Environment) -> Result<Step, EvalError> {
+    match *expr {
+        Sexpr::Integer(n)      => Ok(Step::Done(Value::Integer(n))),
+        Sexpr::BigInt(ref n)   => Ok(Step::Done(Value::BigInt(n.clone()))),
+        Sexpr::Rational(ref n) => Ok(Step::Done(Value::Rational(n.clone()))),
+        Sexpr::Float(n)        => Ok(Step::Done(Value::Float(n))),
+        Sexpr::Str(ref s)      => Ok(Step::Done(Value::Str(s.clone()))),
+        Sexpr::Bool(b)         => Ok(Step::Done(Value::Bool(b))),
+        Sexpr::Char(c)         => Ok(Step::Done(Value::Char(c))),
+        Sexpr::Symbol(ref s)   => env.get(s).map(Step::Done).ok_or_else(|| EvalError::Unbound(s.clone())),
+        Sexpr::List(ref l)     => eval_list(l, env),
+        Sexpr::Vector(_)       => Ok(Step::Done(Value::from_datum(expr))),
+        Sexpr::DottedList(..)  => Err(EvalError::BadSyntax("cannot evaluate a dotted list".to_string()))
+    }
+}
+
+fn eval_list(list: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    let (head, args) = match list.split_first() {
+        Some(split) => split,
+        None        => return Err(EvalError::BadSyntax("empty combination ()".to_string()))
+    };
+
+    if let Sexpr::Symbol(ref name) = *head {
+        match name.as_str() {
+            "quote"         => return eval_quote(args).map(Step::Done),
+            "quasiquote"    => return eval_quasiquote(args, env).map(Step::Done),
+            "define"        => return eval_define(args, env).map(Step::Done),
+            "define-syntax" => return eval_define_syntax(args, env).map(Step::Done),
+            "set!"          => return eval_set(args, env).map(Step::Done),
+            "lambda"        => return eval_lambda(args, env).map(Step::Done),
+            "if"            => return eval_if(args, env),
+            "when"          => return eval_when("when", true, args, env),
+            "unless"        => return eval_when("unless", false, args, env),
+            "begin"         => return eval_sequence(args, env),
+            "cond"          => return eval_cond(args, env),
+            "and"           => return eval_and(args, env),
+            "or"            => return eval_or(args, env),
+            "let"           => return eval_let(args, env),
+            "let*"          => return eval_let_star(args, env),
+            "letrec"        => return eval_letrec(args, env),
+            "let-values"    => return eval_let_values(args, env),
+            "guard"         => return eval_guard(args, env),
+            _               => ()
+        }
+    }
+
+    let procedure = eval_one(head, env)?;
+    if let Value::Macro(ref transformer) = procedure {
+        let expansion = transformer.expand(list)?;
+        return eval_step(&expansion, env)
+    }
+
+    let mut values = Vec::with_capacity(args.len());
+    for arg in args {
+        values.push(eval_one(arg, env)?);
+    }
+    Ok(Step::TailCall(procedure, values))
+}
+
+/* start a call; a procedure body's last expression is returned unevaluated as the next step */
+fn call(procedure: &Value, args: Vec<Value>, env: &mut Environment) -> Result<Step, EvalError> {
+    match *procedure {
+        Value::Primitive(ref primitive)    => (primitive.func)(&args, env).map(Step::Done),
+        Value::NativeProcedure(ref native) => (native.func)(&args).map(Step::Done),
+        Value::Continuation(ref k)         => match args.len() {
+            1 => Err(EvalError::Escape(k.clone(), args.into_iter().next().unwrap())),
+            n => Err(EvalError::ArityMismatch { expected: 1, got: n })
+        },
+        Value::Procedure(ref lambda)       => {
+            let arity = lambda.params.len();
+            if args.len() < arity || (args.len() > arity && lambda.rest.is_none()) {
+                return Err(EvalError::ArityMismatch { expected: arity, got: args.len() })
+            }
+
+            let mut scope = lambda.env.child();
+            let mut args  = args.into_iter();
+            for (param, arg) in lambda.params.iter().zip(args.by_ref()) {
+                scope.define(param, arg);
+            }
+            if let Some(ref rest) = lambda.rest {
+                scope.define(rest, Value::list(args.collect()));
+            }
+
+            eval_sequence(&lambda.body, &mut scope)
+        },
+        _ => Err(EvalError::TypeError(format!("not a procedure: {:?}", procedure)))
+    }
+}
+
+/* (quote datum) */
+fn eval_quote(args: &[Sexpr]) -> Result<Value, EvalError> {
+    match args {
+        [datum] => Ok(Value::from_datum(datum)),
+        _       => Err(EvalError::BadSyntax("quote: expected exactly one datum".to_string()))
+    }
+}
+
+/* (quasiquote template), like quote but (unquote x) is replaced by the value of x and
+   (unquote-splicing x) by the elements of the list x evaluates to */
+fn eval_quasiquote(args: &[Sexpr], env: &mut Environment) -> Result<Value, EvalError> {
+    match args {
+        [template] => quasiquote(template, 1, env),
+        _          => Err(EvalError::BadSyntax("quasiquote: expected exactly one template".to_string()))
+    }
+}
+
+/* `depth` counts the quasiquotes around `template`, only unquotes at depth 1 are evaluated */
+fn quasiquote(template: &Sexpr, depth: usize, env: &mut Environment) -> Result<Value, EvalError> {
+    match *template {
+        Sexpr::List(ref items) => match items.as_slice() {
+            [Sexpr::Symbol(name), expr] if name == "unquote" => {
+                if depth == 1 {
+                    eval_one(expr, env)
+                } else {
+                    nested_quasiquote(name, expr, depth - 1, env)
+                }
+            },
+            [Sexpr::Symbol(name), expr] if name == "quasiquote" => nested_quasiquote(name, expr, depth + 1, env),
+            _ => Ok(Value::list(quasiquote_items(items, depth, env)?))
+        },
+        Sexpr::DottedList(ref items, ref tail) => {
+            let tail = quasiquote(tail, depth, env)?;
+            Ok(quasiquote_items(items, depth, env)?.into_iter().rev().fold(tail, |cdr, car| Value::cons(car, cdr)))
+        },
+        Sexpr::Vector(ref items) => Ok(Value::vector(quasiquote_items(items, depth, env)?)),
+        _ => Ok(Value::from_datum(template))
+    }
+}
+
+/* rebuild `(name expr)` at the new depth */
+fn nested_quasiquote(name: &str, expr: &Sexpr, depth: usize, env: &mut Environment) -> Result<Value, EvalError> {
+    Ok(Value::list(vec![Value::Symbol(name.to_string()), quasiquote(expr, depth, env)?]))
+}
+
+fn quasiquote_items(items: &[Sexpr], depth: usize, env: &mut Environment) -> Result<Vec<Value>, EvalError> {
+    let mut values = vec![];
+    for item in items {
+        match *item {
+            Sexpr::List(ref splice) => match splice.as_slice() {
+                [Sexpr::Symbol(name), expr] if name == "unquote-splicing" && depth == 1 => {
+                    let list = eval_one(expr, env)?;
+                    match list.list_to_vec() {
+                        Some(elements) => values.extend(elements),
+                        None           => return Err(EvalError::TypeError(format!("unquote-splicing: not a list: {:?}", list)))
+                    }
+                },
+                [Sexpr::Symbol(name), expr] if name == "unquote-splicing" => {
+                    values.push(nested_quasiquote(name, expr, depth - 1, env)?)
+                },
+                _ => values.push(quasiquote(item, depth, env)?)
+            },
+            _ => values.push(quasiquote(item, depth, env)?)
+        }
+    }
+    Ok(values)
+}
+
+/* evaluate each expression in order, the last in tail position, Nil when empty */
+fn eval_sequence(exprs: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    match exprs.split_last() {
+        Some((last, init)) => {
+            for expr in init {
+                eval(expr, env)?;
+            }
+            eval_step(last, env)
+        },
+        None => Ok(Step::Done(Value::Nil))
+    }
+}
+
+/* (if test then) or (if test then else), a missing else yields Nil */
+fn eval_if(args: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    if args.len() != 2 && args.len() != 3 {
+        return Err(EvalError::BadSyntax("if: expected (if test then [else])".to_string()))
+    }
+
+    if eval_one(&args[0], env)?.is_true() {
+        eval_step(&args[1], env)
+    } else if args.len() == 3 {
+        eval_step(&args[2], env)
+    } else {
+        Ok(Step::Done(Value::Nil))
+    }
+}
+
+/* (when test body...) runs the body only if test is true, (unless test body...) only if it's false */
+fn eval_when(form: &str, run_when: bool, args: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    let (test, body) = match args.split_first() {
+        Some((test, body)) if !body.is_empty() => (test, body),
+        _ => return Err(EvalError::BadSyntax(format!("{}: expected ({} test body...)", form, form)))
+    };
+
+    if eval_one(test, env)?.is_true() == run_when {
+        eval_sequence(body, env)
+    } else {
+        Ok(Step::Done(Value::Nil))
+    }
+}
+
+/* (cond (test body...) ... (else body...)), a clause without a body yields its test value */
+fn eval_cond(clauses: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    Ok(cond_clauses(clauses, env)?.unwrap_or(Step::Done(Value::Nil)))
+}
+
+/* the body of the first clause whose test is true, None if there isn't one */
+fn cond_clauses(clauses: &[Sexpr], env: &mut Environment) -> Result<Option<Step>, EvalError> {
+    for clause in clauses {
+        let (test, body) = match *clause {
+            Sexpr::List(ref l) if !l.is_empty() => (&l[0], &l[1..]),
+            _ => return Err(EvalError::BadSyntax("cond: clauses must be non-empty lists".to_string()))
+        };
+
+        let value = match *test {
+            Sexpr::Symbol(ref s) if s == "else" => Value::Bool(true),
+            _                                   => eval_one(test, env)?
+        };
+
+        if value.is_true() {
+            return if body.is_empty() { Ok(Some(Step::Done(value))) } else { eval_sequence(body, env).map(Some) }
+        }
+    }
+    Ok(None)
+}
+
+/* (guard (var clause...) body...) evaluates the body, and if it fails binds the error to `var` and
+   picks a cond clause to handle it. when no clause applies the error carries on up, as do
+   continuations escaping through it */
+fn eval_guard(args: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    let (name, clauses, body) = match args.split_first() {
+        Some((Sexpr::List(spec), body)) => match spec.split_first() {
+            Some((Sexpr::Symbol(name), clauses)) => (name, clauses, body),
+            _ => return Err(EvalError::BadSyntax("guard: expected (variable clause...)".to_string()))
+        },
+        _ => return Err(EvalError::BadSyntax("guard: expected (variable clause...) and a body".to_string()))
+    };
+
+    let err = match eval_sequence(body, env).and_then(|step| run(step, env)) {
+        Ok(value)                        => return Ok(Step::Done(value)),
+        Err(err @ EvalError::Escape(..)) => return Err(err),
+        Err(err)                         => err
+    };
+
+    let mut scope = env.child();
+    scope.define(name, Value::Condition(Rc::new(err.clone())));
+    match cond_clauses(clauses, &mut scope)? {
+        Some(step) => Ok(step),
+        None       => Err(err)
+    }
+}
+
+/* (and exprs...), the first false value or the last value, #t when empty */
+fn eval_and(args: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    match args.split_last() {
+        Some((last, init)) => {
+            for expr in init {
+                let value = eval_one(expr, env)?;
+                if !value.is_true() {
+                    return Ok(Step::Done(value))
+                }
+            }
+            eval_step(last, env)
+        },
+        None => Ok(Step::Done(Value::Bool(true)))
+    }
+}
+
+/* (or exprs...), the first true value or the last value, #f when empty */
+fn eval_or(args: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    match args.split_last() {
+        Some((last, init)) => {
+            for expr in init {
+                let value = eval_one(expr, env)?;
+                if value.is_true() {
+                    return Ok(Step::Done(value))
+                }
+            }
+            eval_step(last, env)
+        },
+        None => Ok(Step::Done(Value::Bool(false)))
+    }
+}
+
+type Bindings<'a> = Vec<(&'a str, &'a Sexpr)>;
+
+/* split `((name init) ...) body...` into its bindings and a non-empty body */
+fn let_parts<'a>(form: &str, args: &'a [Sexpr]) -> Result<(Bindings<'a>, &'a [Sexpr]), EvalError> {
+    let (bindings, body) = match args.split_first() {
+        Some((Sexpr::List(bindings), body)) if !body.is_empty() => (bindings, body),
+        _ => return Err(EvalError::BadSyntax(format!("{}: expected a binding list and a body", form)))
+    };
+
+    let mut parts = vec![];
+    for binding in bindings {
+        match *binding {
+            Sexpr::List(ref pair) => match pair.as_slice() {
+                [Sexpr::Symbol(name), init] => parts.push((name.as_str(), init)),
+                _ => return Err(EvalError::BadSyntax(format!("{}: bindings must be (name init)", form)))
+            },
+            _ => return Err(EvalError::BadSyntax(format!("{}: bindings must be (name init)", form)))
+        }
+    }
+    Ok((parts, body))
+}
+
+/* (let ((name init) ...) body...), inits are all evaluated in the enclosing scope */
+fn eval_let(args: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    if let Some((Sexpr::Symbol(name), rest)) = args.split_first() {
+        return eval_named_let(name, rest, env)
+    }
+
+    let (bindings, body) = let_parts("let", args)?;
+    let mut values = Vec::with_capacity(bindings.len());
+    for &(_, init) in &bindings {
+        values.push(eval_one(init, env)?);
+    }
+
+    let mut scope = env.child();
+    for (&(name, _), value) in bindings.iter().zip(values) {
+        scope.define(name, value);
+    }
+    eval_sequence(body, &mut scope)
+}
+
+/* (let loop ((name init) ...) body...) binds `loop` to a procedure over the names with the body,
+   visible only inside the body, then calls it with the inits. calls to `loop` in tail position iterate */
+fn eval_named_let(name: &str, args: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    let (bindings, body) = let_parts("let", args)?;
+    let mut values = Vec::with_capacity(bindings.len());
+    for &(_, init) in &bindings {
+        values.push(eval_one(init, env)?);
+    }
+
+    let params    = bindings.iter().map(|&(name, _)| Sexpr::Symbol(name.to_string())).collect::<Vec<_>>();
+    let mut scope = env.child();
+    let procedure = make_lambda(&params, None, body, &scope)?;
+    scope.define(name, procedure.clone());
+    Ok(Step::TailCall(procedure, values))
+}
+
+/* (let* ((name init) ...) body...), each init sees the names bound before it */
+fn eval_let_star(args: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    let (bindings, body) = let_parts("let*", args)?;
+    let mut scope = env.child();
+    for (name, init) in bindings {
+        let value = eval_one(init, &mut scope)?;
+        scope.define(name, value);
+    }
+    eval_sequence(body, &mut scope)
+}
+
+/* (letrec ((name init) ...) body...), every init sees every name so they can be mutually recursive */
+fn eval_letrec(args: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    let (bindings, body) = let_parts("letrec", args)?;
+    let mut scope = env.child();
+    for &(name, _) in &bindings {
+        scope.define(name, Value::Nil);
+    }
+    for (name, init) in bindings {
+        let value = eval_one(init, &mut scope)?;
+        scope.define(name, value);
+    }
+    eval_sequence(body, &mut scope)
+}
+
+/* (let-values (((names...) init) ...) body...) binds the names to the values each init returns,
+   which are all evaluated in the enclosing scope. the names can be any lambda parameter list */
+fn eval_let_values(args: &[Sexpr], env: &mut Environment) -> Result<Step, EvalError> {
+    let (bindings, body) = match args.split_first() {
+        Some((Sexpr::List(bindings), body)) if !body.is_empty() => (bindings, body),
+        _ => return Err(EvalError::BadSyntax("let-values: expected a binding list and a body".to_string()))
+    };
+
+    let mut values = Vec::with_capacity(bindings.len());
+    for binding in bindings {
+        match *binding {
+            Sexpr::List(ref pair) if pair.len() == 2 => values.push((&pair[0], eval(&pair[1], env)?.into_values())),
+            _ => return Err(EvalError::BadSyntax("let-values: bindings must be (formals init)".to_string()))
+        }
+    }
+
+    let mut scope = env.child();
+    for (formals, values) in values {
+        bind_formals(formals, values, &mut scope)?;
+    }
+    eval_sequence(body, &mut scope)
+}
+
+/* define each name in a parameter list to its value, a rest parameter gets the leftovers as a list */
+fn bind_formals(formals: &Sexpr, values: Vec<Value>, scope: &mut Environment) -> Result<(), EvalError> {
+    let (params, rest) = match *formals {
+        Sexpr::List(ref params)                 => (params.as_slice(), None),
+        Sexpr::DottedList(ref params, ref rest) => (params.as_slice(), Some(&**rest)),
+        Sexpr::Symbol(_)                        => (&[][..], Some(formals)),
+        _ => return Err(EvalError::BadSyntax("expected a parameter list".to_string()))
+    };
+
+    if values.len() < params.len() || (values.len() > params.len() && rest.is_none()) {
+        return Err(EvalError::ArityMismatch { expected: params.len(), got: values.len() })
+    }
+
+    let mut values = values.into_iter();
+    for (param, value) in params.iter().zip(values.by_ref()) {
+        scope.define(&param_name(param)?, value);
+    }
+    if let Some(rest) = rest {
+        scope.define(&param_name(rest)?, Value::list(values.collect()));
+    }
+    Ok(())
+}
+
+/* (lambda (params...) body...), (lambda (params... . rest) body...) or (lambda args body...) */
+fn eval_lambda(args: &[Sexpr], env: &mut Environment) -> Result<Value, EvalError> {
+    match args.split_first() {
+        Some((Sexpr::List(params), body))             => make_lambda(params, None, body, env),
+        Some((Sexpr::DottedList(params, rest), body)) => make_lambda(params, Some(rest), body, env),
+        Some((rest @ Sexpr::Symbol(_), body))         => make_lambda(&[], Some(rest), body, env),
+        _ => Err(EvalError::BadSyntax("lambda: expected a parameter list".to_string()))
+    }
+}
+
+/* (define name expr), (define (name params...) body...) or (define (name params... . rest) body...) */
+fn eval_define(args: &[Sexpr], env: &mut Environment) -> Result<Value, EvalError> {
+    match args.split_first() {
+        Some((Sexpr::Symbol(name), rest)) => {
+            if rest.len() != 1 {
+                return Err(EvalError::BadSyntax(format!("define: expected one value for {}", name)))
+            }
+            let value = eval_one(&rest[0], env)?;
+            env.define(name, value);
+            Ok(Value::Symbol(name.clone()))
+        },
+        Some((Sexpr::List(signature), body))             => define_procedure(signature, None, body, env),
+        Some((Sexpr::DottedList(signature, rest), body)) => define_procedure(signature, Some(rest), body, env),
+        _ => Err(EvalError::BadSyntax("define: expected a name".to_string()))
+    }
+}
+
+/* (set! name expr), only rebinds a name that is already defined */
+fn eval_set(args: &[Sexpr], env: &mut Environment) -> Result<Value, EvalError> {
+    match args {
+        [Sexpr::Symbol(name), expr] => {
+            let value = eval_one(expr, env)?;
+            if env.set(name, value) {
+                Ok(Value::Nil)
+            } else {
+                Err(EvalError::Unbound(name.clone()))
+            }
+        },
+        _ => Err(EvalError::BadSyntax("set!: expected (set! name value)".to_string()))
+    }
+}
+
+/* (define-syntax name (syntax-rules (literal ...) (pattern template) ...)) */
+fn eval_define_syntax(args: &[Sexpr], env: &mut Environment) -> Result<Value, EvalError> {
+    match args {
+        [Sexpr::Symbol(name), spec] => {
+            let transformer = Macro::new(name, spec)?;
+            env.define(name, Value::Macro(Rc::new(transformer)));
+            Ok(Value::Symbol(name.clone()))
+        },
+        _ => Err(EvalError::BadSyntax("define-syntax: expected a name and a syntax-rules form".to_string()))
+    }
+}
+
+/* bind a lambda built from the `(name params...)` signature of a define */
+fn define_procedure(signature: &[Sexpr], rest: Option<&Sexpr>, body: &[Sexpr], env: &mut Environment) -> Result<Value, EvalError> {
+    match signature.split_first() {
+        Some((Sexpr::Symbol(name), params)) => {
+            let lambda = make_lambda(params, rest, body, env)?;
+            env.define(name, lambda);
+            Ok(Value::Symbol(name.clone()))
+        },
+        _ => Err(EvalError::BadSyntax("define: expected a procedure name".to_string()))
+    }
+}
+
+fn make_lambda(params: &[Sexpr], rest: Option<&Sexpr>, body: &[Sexpr], env: &Environment) -> Result<Value, EvalError> {
+    let mut names = vec![];
+    for param in params {
+        names.push(param_name(param)?);
+    }
+    let rest = match rest {
+        Some(param) => Some(param_name(param)?),
+        None        => None
+    };
+
+    if body.is_empty() {
+        return Err(EvalError::BadSyntax("procedure body cannot be empty".to_string()))
+    }
+
+    Ok(Value::Procedure(Rc::new(Lambda { params: names, rest, body: body.to_vec(), env: env.clone() })))
+}
+
+fn param_name(param: &Sexpr) -> Result<String, EvalError> {
+    match *param {
+        Sexpr::Symbol(ref name) => Ok(name.clone()),
+        _                       => Err(EvalError::BadSyntax("parameters must be symbols".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parser::read_str;
+
+    fn run(source: &str, env: &mut Environment) -> Result<Value, EvalError> {
+        eval(&read_str(source).unwrap(), env)
+    }
+
+    #[test]
+    fn self_evaluating() {
+        let mut env = Environment::new();
+        assert_eq!(run("42", &mut env), Ok(Value::Integer(42)));
+        assert_eq!(run("-1.5", &mut env), Ok(Value::Float(-1.5)));
+        assert_eq!(run("\"hi\"", &mut env), Ok(Value::Str("hi".to_string())));
+        assert_eq!(run("#t", &mut env), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn bound_symbol() {
+        let mut env = Environment::new();
+        env.define("x", Value::Integer(7));
+        assert_eq!(run("x", &mut env), Ok(Value::Integer(7)));
+    }
+
+    #[test]
+    fn unbound_symbol() {
+        let mut env = Environment::new();
+        assert_eq!(run("x", &mut env), Err(EvalError::Unbound("x".to_string())));
+    }
+
+    #[test]
+    fn define_value() {
+        let mut env = Environment::new();
+        assert_eq!(run("(define x 10)", &mut env), Ok(Value::Symbol("x".to_string())));
+        assert_eq!(run("x", &mut env), Ok(Value::Integer(10)));
+    }
+
+    #[test]
+    fn define_evaluates_value() {
+        let mut env = Environment::new();
+        run("(define x 10)", &mut env).unwrap();
+        run("(define y x)", &mut env).unwrap();
+        assert_eq!(env.get("y"), Some(Value::Integer(10)));
+    }
+
+    #[test]
+    fn quasiquote() {
+        let mut env = Environment::global();
+        assert_eq!(run("`(1 ,(+ 1 1) 3)", &mut env), Ok(int_list(&[1, 2, 3])));
+        assert_eq!(run("`(1 ,@(list 2 3) 4)", &mut env), Ok(int_list(&[1, 2, 3, 4])));
+        assert_eq!(run("`(,@'() 1 ,@'())", &mut env), Ok(int_list(&[1])));
+        assert_eq!(run("`(1 . ,(+ 1 1))", &mut env), Ok(Value::cons(Value::Integer(1), Value::Integer(2))));
+        assert_eq!(run("`#(1 ,(+ 1 1) ,@(list 3))", &mut env), Ok(Value::vector(vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)])));
+        assert_eq!(run("`x", &mut env), Ok(Value::Symbol("x".to_string())));
+        assert_eq!(run("`,(* 2 3)", &mut env), Ok(Value::Integer(6)));
+        assert_eq!(run("`(1 (2 ,(+ 1 2)))", &mut env), Ok(Value::list(vec![Value::Integer(1), int_list(&[2, 3])])));
+    }
+
+    #[test]
+    fn nested_quasiquote() {
+        let mut env = Environment::global();
+        run("(define x 5)", &mut env).unwrap();
+        assert_eq!(run("`(a `(b ,(c ,x)))", &mut env).unwrap().to_string(), "(a (quasiquote (b (unquote (c 5)))))");
+        assert_eq!(run("`(a `(b ,@(c ,@(list x x))))", &mut env).unwrap().to_string(), "(a (quasiquote (b (unquote-splicing (c 5 5)))))");
+    }
+
+    #[test]
+    fn quasiquote_errors() {
+        let mut env = Environment::global();
+        assert!(run("`(1 ,@2)", &mut env).is_err());
+        assert!(run("(quasiquote)", &mut env).is_err());
+    }
+
+    #[test]
+    fn guard() {
+        let mut env = Environment::global();
+        assert_eq!(run("(guard (e (#t 'caught)) (error \"boom\" 1 2))", &mut env), Ok(Value::Symbol("caught".to_string())));
+        assert_eq!(run("(guard (e (#t (error-object-irritants e))) (+ 1 (error \"boom\" 1 2)))", &mut env), Ok(int_list(&[1, 2])));
+        assert_eq!(run("(guard (e ((string? e) 'string) (else (error-object-message e))) (error \"boom\"))", &mut env),
+                   Ok(Value::Str("boom".to_string())));
+        assert_eq!(run("(guard (e (#t 'unused)) 1 2)", &mut env), Ok(Value::Integer(2)));
+    }
+
+    #[test]
+    fn guard_catches_builtin_errors() {
+        let mut env = Environment::global();
+        assert_eq!(run("(guard (e ((error-object? e) 'oops)) (car 1))", &mut env), Ok(Value::Symbol("oops".to_string())));
+        assert_eq!(run("(guard (e (#t (error-object-message e))) undefined)", &mut env),
+                   Ok(Value::Str("unbound variable: undefined".to_string())));
+    }
+
+    #[test]
+    fn guard_reraises() {
+        let mut env = Environment::global();
+        let result  = run("(guard (e (#f 'never)) (error \"boom\" 'x))", &mut env);
+        assert_eq!(result, Err(EvalError::UserError { message: "boom".to_string(), irritants: vec![Value::Symbol("x".to_string())] }));
+        assert!(run("(guard (e) (car '()))", &mut env).is_err());
+        assert!(run("(guard e 1)", &mut env).is_err());
+    }
+
+    #[test]
+    fn define_procedure() {
+        let mut env = Environment::new();
+        run("(define (id a) a)", &mut env).unwrap();
+        match env.get("id") {
+            Some(Value::Procedure(lambda)) => {
+                assert_eq!(lambda.params, vec!["a".to_string()]);
+                assert_eq!(lambda.body, vec![Sexpr::Symbol("a".to_string())]);
+            },
+            other => panic!("expected a procedure, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn define_bad_syntax() {
+        let mut env = Environment::new();
+        for source in &["(define)", "(define x)", "(define x 1 2)", "(define 1 2)", "(define (f))", "(define (f 1) 1)", "(define () 1)"] {
+            match run(source, &mut env) {
+                Err(EvalError::BadSyntax(_)) => (),
+                other                        => panic!("{} gave {:?}", source, other)
+            }
+        }
+    }
+
+    #[test]
+    fn lambda_call() {
+        let mut env = Environment::new();
+        assert_eq!(run("((lambda (x) x) 5)", &mut env), Ok(Value::Integer(5)));
+        assert_eq!(run("((lambda (a b) b) 1 2)", &mut env), Ok(Value::Integer(2)));
+    }
+
+    #[test]
+    fn lambda_body_returns_last() {
+        let mut env = Environment::new();
+        assert_eq!(run("((lambda () 1 2 3))", &mut env), Ok(Value::Integer(3)));
+    }
+
+    #[test]
+    fn call_defined_procedure() {
+        let mut env = Environment::new();
+        run("(define (id a) a)", &mut env).unwrap();
+        assert_eq!(run("(id \"x\")", &mut env), Ok(Value::Str("x".to_string())));
+    }
+
+    #[test]
+    fn closure_captures_scope() {
+        let mut env = Environment::new();
+        run("(define (constantly n) (lambda () n))", &mut env).unwrap();
+        run("(define five (constantly 5))", &mut env).unwrap();
+        run("(define six (constantly 6))", &mut env).unwrap();
+        assert_eq!(env.get("n"), None);
+        assert_eq!(run("(five)", &mut env), Ok(Value::Integer(5)));
+        assert_eq!(run("(six)", &mut env), Ok(Value::Integer(6)));
+    }
+
+    #[test]
+    fn arguments_do_not_leak() {
+        let mut env = Environment::new();
+        run("((lambda (x) x) 1)", &mut env).unwrap();
+        assert_eq!(run("x", &mut env), Err(EvalError::Unbound("x".to_string())));
+    }
+
+    #[test]
+    fn arity_mismatch() {
+        let mut env = Environment::new();
+        assert_eq!(run("((lambda (x) x))", &mut env), Err(EvalError::ArityMismatch { expected: 1, got: 0 }));
+        assert_eq!(run("((lambda (x) x) 1 2)", &mut env), Err(EvalError::ArityMismatch { expected: 1, got: 2 }));
+    }
+
+    #[test]
+    fn call_non_procedure() {
+        let mut env = Environment::new();
+        match run("(1 2)", &mut env) {
+            Err(EvalError::TypeError(_)) => (),
+            other                        => panic!("expected a type error, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn lambda_bad_syntax() {
+        let mut env = Environment::new();
+        for source in &["(lambda)", "(lambda x)", "(lambda (x))", "(lambda (1) 1)"] {
+            match run(source, &mut env) {
+                Err(EvalError::BadSyntax(_)) => (),
+                other                        => panic!("{} gave {:?}", source, other)
+            }
+        }
+    }
+
+    #[test]
+    fn if_form() {
+        let mut env = Environment::new();
+        assert_eq!(run("(if #f 1 2)", &mut env), Ok(Value::Integer(2)));
+        assert_eq!(run("(if #t 1 2)", &mut env), Ok(Value::Integer(1)));
+        assert_eq!(run("(if #f 1)", &mut env), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn if_truthiness() {
+        let mut env = Environment::new();
+        assert_eq!(run("(if 0 1 2)", &mut env), Ok(Value::Integer(1)));
+        assert_eq!(run("(if \"\" 1 2)", &mut env), Ok(Value::Integer(1)));
+    }
+
+    #[test]
+    fn if_evaluates_one_branch() {
+        let mut env = Environment::new();
+        assert_eq!(run("(if #t 1 unbound)", &mut env), Ok(Value::Integer(1)));
+        assert_eq!(run("(if #f unbound 2)", &mut env), Ok(Value::Integer(2)));
+    }
+
+    #[test]
+    fn when_unless() {
+        let mut env = Environment::global();
+        assert_eq!(run("(when #t 1 2 3)", &mut env), Ok(Value::Integer(3)));
+        assert_eq!(run("(when #f unbound)", &mut env), Ok(Value::Nil));
+        assert_eq!(run("(unless #f 1 2 3)", &mut env), Ok(Value::Integer(3)));
+        assert_eq!(run("(unless 0 unbound)", &mut env), Ok(Value::Nil));
+        assert_eq!(run("(begin (define x 0) (when (= x 0) (set! x 1) (set! x (+ x 1))) x)", &mut env), Ok(Value::Integer(2)));
+        assert!(run("(when #t)", &mut env).is_err());
+        assert!(run("(unless)", &mut env).is_err());
+    }
+
+    #[test]
+    fn tail_recursion_in_when() {
+        let mut env = Environment::global();
+        run("(define (count n) (unless (= n 0) (count (- n 1))))", &mut env).unwrap();
+        assert_eq!(run("(count 100000)", &mut env), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn begin_form() {
+        let mut env = Environment::new();
+        assert_eq!(run("(begin 1 2 3)", &mut env), Ok(Value::Integer(3)));
+        assert_eq!(run("(begin (define x 4) x)", &mut env), Ok(Value::Integer(4)));
+        assert_eq!(run("(begin)", &mut env), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn cond_form() {
+        let mut env = Environment::new();
+        assert_eq!(run("(cond (#f 1) (#t 2) (else 3))", &mut env), Ok(Value::Integer(2)));
+        assert_eq!(run("(cond (#f 1) (else 2 3))", &mut env), Ok(Value::Integer(3)));
+        assert_eq!(run("(cond (#f 1) (5))", &mut env), Ok(Value::Integer(5)));
+        assert_eq!(run("(cond (#f 1))", &mut env), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn bad_control_syntax() {
+        let mut env = Environment::new();
+        for source in &["(if)", "(if #t)", "(if #t 1 2 3)", "(cond ())", "(cond 1)"] {
+            match run(source, &mut env) {
+                Err(EvalError::BadSyntax(_)) => (),
+                other                        => panic!("{} gave {:?}", source, other)
+            }
+        }
+    }
+
+    #[test]
+    fn quote() {
+        let mut env = Environment::new();
+        assert_eq!(run("'x", &mut env), Ok(Value::Symbol("x".to_string())));
+        assert_eq!(run("'()", &mut env), Ok(Value::Nil));
+        assert_eq!(run("(quote (a 1))", &mut env), Ok(Value::list(vec![Value::Symbol("a".to_string()), Value::Integer(1)])));
+        match run("(quote)", &mut env) {
+            Err(EvalError::BadSyntax(_)) => (),
+            other                        => panic!("expected bad syntax, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn let_form() {
+        let mut env = Environment::global();
+        assert_eq!(run("(let ((x 1) (y 2)) (+ x y))", &mut env), Ok(Value::Integer(3)));
+        assert_eq!(run("(let () 5)", &mut env), Ok(Value::Integer(5)));
+        assert_eq!(run("x", &mut env), Err(EvalError::Unbound("x".to_string())));
+    }
+
+    #[test]
+    fn let_binds_simultaneously() {
+        let mut env = Environment::global();
+        assert_eq!(run("(let ((x 1)) (let ((x 2) (y x)) y))", &mut env), Ok(Value::Integer(1)));
+    }
+
+    #[test]
+    fn let_star_binds_sequentially() {
+        let mut env = Environment::global();
+        assert_eq!(run("(let ((x 1)) (let* ((x 2) (y x)) y))", &mut env), Ok(Value::Integer(2)));
+        assert_eq!(run("(let* ((x 1) (x (+ x 1))) x)", &mut env), Ok(Value::Integer(2)));
+    }
+
+    #[test]
+    fn letrec_mutual_recursion() {
+        let mut env = Environment::global();
+        let source  = "(letrec ((even? (lambda (n) (if (= n 0) #t (odd? (- n 1))))) \
+                               (odd?  (lambda (n) (if (= n 0) #f (even? (- n 1)))))) \
+                         (list (even? 10) (odd? 7) (even? 3)))";
+        assert_eq!(run(source, &mut env), Ok(Value::list(vec![Value::Bool(true), Value::Bool(true), Value::Bool(false)])));
+    }
+
+    #[test]
+    fn let_values() {
+        let mut env = Environment::global();
+        assert_eq!(run("(let-values (((q r) (values 7 2))) (list q r))", &mut env), Ok(Value::list(vec![Value::Integer(7), Value::Integer(2)])));
+        let source = "(let ((x 1)) (let-values (((x . rest) (values 2 3 4)) (all (values x))) (list x rest all)))";
+        assert_eq!(run(source, &mut env), run("'(2 (3 4) (1))", &mut env));
+        assert_eq!(run("(let-values (((a b) (values 1))) a)", &mut env), Err(EvalError::ArityMismatch { expected: 2, got: 1 }));
+    }
+
+    #[test]
+    fn named_let() {
+        let mut env = Environment::global();
+        let source  = "(let loop ((i 0) (acc 0)) (if (= i 101) acc (loop (+ i 1) (+ acc i))))";
+        assert_eq!(run(source, &mut env), Ok(Value::Integer(5050)));
+        assert_eq!(run("(let loop () 1)", &mut env), Ok(Value::Integer(1)));
+        assert_eq!(run("(let f ((x 1)) f)", &mut env).map(|f| matches!(f, Value::Procedure(_))), Ok(true));
+        assert_eq!(env.get("loop"), None);
+    }
+
+    #[test]
+    fn named_let_inits_see_outer_scope() {
+        let mut env = Environment::global();
+        assert_eq!(run("(let ((loop 5)) (let loop ((x loop)) x))", &mut env), Ok(Value::Integer(5)));
+    }
+
+    #[test]
+    fn named_let_tail_calls() {
+        let mut env = Environment::global();
+        let source  = "(let loop ((i 0)) (if (= i 1000000) i (loop (+ i 1))))";
+        assert_eq!(run(source, &mut env), Ok(Value::Integer(1000000)));
+    }
+
+    #[test]
+    fn named_let_bad_syntax() {
+        let mut env = Environment::global();
+        assert!(run("(let loop)", &mut env).is_err());
+        assert!(run("(let loop ((x 1)))", &mut env).is_err());
+        assert_eq!(run("(let loop ((x 1)) (loop))", &mut env), Err(EvalError::ArityMismatch { expected: 1, got: 0 }));
+    }
+
+    #[test]
+    fn let_bad_syntax() {
+        let mut env = Environment::global();
+        for source in &["(let)", "(let ((x 1)))", "(let (x) x)", "(let ((x)) x)", "(let* ((1 2)) 1)", "(letrec x 1)"] {
+            match run(source, &mut env) {
+                Err(EvalError::BadSyntax(_)) => (),
+                other                        => panic!("{} gave {:?}", source, other)
+            }
+        }
+    }
+
+    #[test]
+    fn tail_recursion_in_if() {
+        let mut env = Environment::global();
+        run("(define (count n) (if (= n 0) 'done (count (- n 1))))", &mut env).unwrap();
+        assert_eq!(run("(count 1000000)", &mut env), Ok(Value::Symbol("done".to_string())));
+    }
+
+    #[test]
+    fn tail_recursion_in_cond() {
+        let mut env = Environment::global();
+        run("(define (count n) (cond ((= n 0) 'done) (else (count (- n 1)))))", &mut env).unwrap();
+        assert_eq!(run("(count 1000000)", &mut env), Ok(Value::Symbol("done".to_string())));
+    }
+
+    #[test]
+    fn tail_recursion_between_conds() {
+        let mut env = Environment::global();
+        run("(define (even n) (cond ((= n 0) #t) (else (odd (- n 1)))))", &mut env).unwrap();
+        run("(define (odd n) (cond ((= n 0) #f) ((= n 1) #t) (else (even (- n 1)))))", &mut env).unwrap();
+        assert_eq!(run("(even 100001)", &mut env), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn tail_recursion_in_and_or() {
+        let mut env = Environment::global();
+        run("(define (all n) (and #t (or (= n 0) (all (- n 1)))))", &mut env).unwrap();
+        run("(define (none n) (or #f (and (> n 0) (none (- n 1)))))", &mut env).unwrap();
+        assert_eq!(run("(all 100000)", &mut env), Ok(Value::Bool(true)));
+        assert_eq!(run("(none 100000)", &mut env), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn tail_recursion_through_forms() {
+        let mut env = Environment::global();
+        run("(define (loop n acc) \
+               (begin \
+                 (let ((next (- n 1))) \
+                   (cond ((< n 1) acc) \
+                         (else (let* ((m next)) (letrec ((k m)) (loop k (+ acc 1)))))))))", &mut env).unwrap();
+        assert_eq!(run("(loop 100000 0)", &mut env), Ok(Value::Integer(100000)));
+    }
+
+    #[test]
+    fn tail_call_to_primitive() {
+        let mut env = Environment::global();
+        assert_eq!(run("((lambda (x) (+ x 1)) 1)", &mut env), Ok(Value::Integer(2)));
+    }
+
+    #[test]
+    fn apply_procedure() {
+        let mut env = Environment::global();
+        let square  = run("(lambda (x) (* x x))", &mut env).unwrap();
+        assert_eq!(apply(&square, vec![Value::Integer(7)], &mut env), Ok(Value::Integer(49)));
+    }
+
+    fn int_list(values: &[i64]) -> Value {
+        Value::list(values.iter().map(|&n| Value::Integer(n)).collect())
+    }
+
+    #[test]
+    fn rest_parameter() {
+        let mut env = Environment::new();
+        assert_eq!(run("((lambda (a . rest) rest) 1 2 3)", &mut env), Ok(int_list(&[2, 3])));
+        assert_eq!(run("((lambda (a . rest) a) 1 2 3)", &mut env), Ok(Value::Integer(1)));
+        assert_eq!(run("((lambda (a . rest) rest) 1)", &mut env), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn all_arguments_as_list() {
+        let mut env = Environment::new();
+        assert_eq!(run("((lambda args args) 1 2)", &mut env), Ok(int_list(&[1, 2])));
+        assert_eq!(run("((lambda args args))", &mut env), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn define_variadic() {
+        let mut env = Environment::new();
+        run("(define (tail a b . rest) rest)", &mut env).unwrap();
+        run("(define (all . xs) xs)", &mut env).unwrap();
+        assert_eq!(run("(tail 1 2 3 4)", &mut env), Ok(int_list(&[3, 4])));
+        assert_eq!(run("(all 5)", &mut env), Ok(int_list(&[5])));
+    }
+
+    #[test]
+    fn rest_arity_mismatch() {
+        let mut env = Environment::new();
+        assert_eq!(run("((lambda (a b . rest) a) 1)", &mut env), Err(EvalError::ArityMismatch { expected: 2, got: 1 }));
+    }
+
+    #[test]
+    fn quoted_dotted_pair() {
+        let mut env = Environment::new();
+        assert_eq!(run("'(1 . 2)", &mut env), Ok(Value::cons(Value::Integer(1), Value::Integer(2))));
+    }
+
+    #[test]
+    fn set_existing() {
+        let mut env = Environment::global();
+        run("(define x 1)", &mut env).unwrap();
+        assert_eq!(run("(set! x (+ x 1))", &mut env), Ok(Value::Nil));
+        assert_eq!(run("x", &mut env), Ok(Value::Integer(2)));
+    }
+
+    #[test]
+    fn set_through_closure() {
+        let mut env = Environment::global();
+        run("(define (make-counter) (let ((n 0)) (lambda () (set! n (+ n 1)) n)))", &mut env).unwrap();
+        run("(define a (make-counter))", &mut env).unwrap();
+        run("(define b (make-counter))", &mut env).unwrap();
+        assert_eq!(run("(a)", &mut env), Ok(Value::Integer(1)));
+        assert_eq!(run("(a)", &mut env), Ok(Value::Integer(2)));
+        assert_eq!(run("(b)", &mut env), Ok(Value::Integer(1)));
+        assert_eq!(run("(a)", &mut env), Ok(Value::Integer(3)));
+    }
+
+    #[test]
+    fn set_outer_from_inner_scope() {
+        let mut env = Environment::global();
+        run("(define total 0)", &mut env).unwrap();
+        run("(define (add! n) (set! total (+ total n)))", &mut env).unwrap();
+        run("(add! 5)", &mut env).unwrap();
+        run("(add! 7)", &mut env).unwrap();
+        assert_eq!(run("total", &mut env), Ok(Value::Integer(12)));
+    }
+
+    #[test]
+    fn set_unbound() {
+        let mut env = Environment::global();
+        assert_eq!(run("(set! y 1)", &mut env), Err(EvalError::Unbound("y".to_string())));
+        assert_eq!(run("y", &mut env), Err(EvalError::Unbound("y".to_string())));
+    }
+
+    #[test]
+    fn set_bad_syntax() {
+        let mut env = Environment::global();
+        for source in &["(set!)", "(set! x)", "(set! 1 2)", "(set! x 1 2)"] {
+            match run(source, &mut env) {
+                Err(EvalError::BadSyntax(_)) => (),
+                other                        => panic!("{} gave {:?}", source, other)
+            }
+        }
+    }
+
+    #[test]
+    fn and_form() {
+        let mut env = Environment::new();
+        assert_eq!(run("(and 1 2 3)", &mut env), Ok(Value::Integer(3)));
+        assert_eq!(run("(and 1 #f 3)", &mut env), Ok(Value::Bool(false)));
+        assert_eq!(run("(and)", &mut env), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn and_short_circuits() {
+        let mut env = Environment::new();
+        assert_eq!(run("(and 1 #f (error))", &mut env), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn or_form() {
+        let mut env = Environment::new();
+        assert_eq!(run("(or #f 5 6)", &mut env), Ok(Value::Integer(5)));
+        assert_eq!(run("(or #f #f)", &mut env), Ok(Value::Bool(false)));
+        assert_eq!(run("(or)", &mut env), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn or_short_circuits() {
+        let mut env = Environment::new();
+        assert_eq!(run("(or #f 5 (error))", &mut env), Ok(Value::Integer(5)));
+    }
+
+    #[test]
+    fn short_circuit_skips_side_effects() {
+        let mut env = Environment::global();
+        run("(define x 0)", &mut env).unwrap();
+        run("(and #f (set! x 1))", &mut env).unwrap();
+        run("(or 1 (set! x 2))", &mut env).unwrap();
+        assert_eq!(run("x", &mut env), Ok(Value::Integer(0)));
+    }
+}
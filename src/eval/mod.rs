@@ -0,0 +1,20 @@
+pub use self::eval::eval;
+pub use self::eval::apply;
+pub use self::eval::apply_one;
+pub use self::eval::EvalError;
+pub use self::value::Value;
+pub use self::value::Lambda;
+pub use self::value::Continuation;
+pub use self::value::Primitive;
+pub use self::value::Native;
+pub use self::value::PrimitiveFn;
+pub use self::value::NativeFn;
+pub use self::environment::Environment;
+pub use self::syntax::Macro;
+
+mod eval;
+mod value;
+mod environment;
+mod builtins;
+mod number;
+mod syntax;
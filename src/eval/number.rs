@@ -0,0 +1,232 @@
+use bigint::BigInt;
+use eval::EvalError;
+use eval::Value;
+use rational::Rational;
+
+use std::cmp::Ordering;
+
+/* the numeric tower: integers promote to bignums on overflow, inexact division of exact numbers gives a
+   rational, and anything mixed with a float becomes a float. a Big is always outside the range of i64 and a
+   Rational is never a whole number, results are demoted to the simplest exact representation */
+#[derive(PartialEq, Debug, Clone)]
+pub enum Number {
+    Integer(i64),
+    Big(BigInt),
+    Rational(Rational),
+    Float(f64)
+}
+
+impl Number {
+    pub fn from_value(value: &Value) -> Result<Number, EvalError> {
+        match *value {
+            Value::Integer(n)      => Ok(Number::Integer(n)),
+            Value::BigInt(ref n)   => Ok(Number::Big(n.clone())),
+            Value::Rational(ref n) => Ok(Number::Rational(n.clone())),
+            Value::Float(n)        => Ok(Number::Float(n)),
+            _                      => Err(EvalError::TypeError(format!("not a number: {:?}", value)))
+        }
+    }
+
+    pub fn into_value(self) -> Value {
+        match self {
+            Number::Integer(n)  => Value::Integer(n),
+            Number::Big(n)      => Value::BigInt(n),
+            Number::Rational(n) => Value::Rational(n),
+            Number::Float(n)    => Value::Float(n)
+        }
+    }
+
+    fn from_big(n: BigInt) -> Number {
+        match n.to_i64() {
+            Some(n) => Number::Integer(n),
+            None    => Number::Big(n)
+        }
+    }
+
+    fn from_rational(n: Rational) -> Number {
+        if n.is_integer() {
+            Number::from_big(n.numer().clone())
+        } else {
+            Number::Rational(n)
+        }
+    }
+
+    /* None for rationals and floats */
+    fn to_big(&self) -> Option<BigInt> {
+        match *self {
+            Number::Integer(n) => Some(BigInt::from_i64(n)),
+            Number::Big(ref n) => Some(n.clone()),
+            _                  => None
+        }
+    }
+
+    /* None for floats, the only inexact numbers */
+    fn to_rational(&self) -> Option<Rational> {
+        match *self {
+            Number::Rational(ref n) => Some(n.clone()),
+            Number::Float(_)        => None,
+            _                       => self.to_big().map(Rational::from_integer)
+        }
+    }
+
+    pub fn to_float(&self) -> f64 {
+        match *self {
+            Number::Integer(n)      => n as f64,
+            Number::Big(ref n)      => n.to_f64(),
+            Number::Rational(ref n) => n.to_f64(),
+            Number::Float(n)        => n
+        }
+    }
+
+    pub fn add(self, other: Number) -> Number {
+        self.combine(other, i64::checked_add, BigInt::add, Rational::add, |a, b| a + b)
+    }
+
+    pub fn sub(self, other: Number) -> Number {
+        self.combine(other, i64::checked_sub, BigInt::sub, Rational::sub, |a, b| a - b)
+    }
+
+    pub fn mul(self, other: Number) -> Number {
+        self.combine(other, i64::checked_mul, BigInt::mul, Rational::mul, |a, b| a * b)
+    }
+
+    /* dividing exact numbers gives an exact result, a whole number when it divides evenly */
+    pub fn div(self, other: Number) -> Result<Number, EvalError> {
+        match (&self, &other) {
+            (_, &Number::Integer(0))                    => return Err(EvalError::DivideByZero),
+            (&Number::Integer(a), &Number::Integer(b)) if a.checked_rem(b) == Some(0) => return Ok(Number::Integer(a / b)),
+            _                                           => ()
+        }
+
+        match (self.to_rational(), other.to_rational()) {
+            (Some(a), Some(b)) => a.div(&b).map(Number::from_rational).ok_or(EvalError::DivideByZero),
+            _                  => Ok(Number::Float(self.to_float() / other.to_float()))
+        }
+    }
+
+    /* None only when a NaN is involved */
+    pub fn compare(&self, other: &Number) -> Option<Ordering> {
+        if let (&Number::Integer(a), &Number::Integer(b)) = (self, other) {
+            return Some(a.cmp(&b))
+        }
+
+        match (self.to_rational(), other.to_rational()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            _                  => self.to_float().partial_cmp(&other.to_float())
+        }
+    }
+
+    /* use the cheapest representation that can hold both operands exactly */
+    fn combine<I, B, R, F>(self, other: Number, int_op: I, big_op: B, rational_op: R, float_op: F) -> Number
+        where I: Fn(i64, i64) -> Option<i64>,
+              B: Fn(&BigInt, &BigInt) -> BigInt,
+              R: Fn(&Rational, &Rational) -> Rational,
+              F: Fn(f64, f64) -> f64 {
+        if let (&Number::Integer(a), &Number::Integer(b)) = (&self, &other) {
+            if let Some(n) = int_op(a, b) {
+                return Number::Integer(n)
+            }
+        }
+
+        if let (Some(a), Some(b)) = (self.to_big(), other.to_big()) {
+            return Number::from_big(big_op(&a, &b))
+        }
+
+        match (self.to_rational(), other.to_rational()) {
+            (Some(a), Some(b)) => Number::from_rational(rational_op(&a, &b)),
+            _                  => Number::Float(float_op(self.to_float(), other.to_float()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_arithmetic() {
+        assert_eq!(Number::Integer(2).add(Number::Integer(3)), Number::Integer(5));
+        assert_eq!(Number::Integer(2).sub(Number::Integer(3)), Number::Integer(-1));
+        assert_eq!(Number::Integer(2).mul(Number::Integer(3)), Number::Integer(6));
+        assert_eq!(Number::Integer(6).div(Number::Integer(3)), Ok(Number::Integer(2)));
+    }
+
+    #[test]
+    fn mixed_promotes() {
+        assert_eq!(Number::Integer(1).add(Number::Float(2.0)), Number::Float(3.0));
+        assert_eq!(Number::Float(1.5).mul(Number::Integer(2)), Number::Float(3.0));
+        assert_eq!(Number::Integer(1).div(Number::Float(2.0)), Ok(Number::Float(0.5)));
+    }
+
+    fn big(s: &str) -> Number {
+        Number::Big(BigInt::parse(s).unwrap())
+    }
+
+    #[test]
+    fn overflow_promotes() {
+        assert_eq!(Number::Integer(i64::MAX).add(Number::Integer(1)), big("9223372036854775808"));
+        assert_eq!(Number::Integer(i64::MIN).sub(Number::Integer(1)), big("-9223372036854775809"));
+        assert_eq!(Number::Integer(i64::MAX).mul(Number::Integer(2)), big("18446744073709551614"));
+        assert_eq!(Number::Integer(i64::MIN).div(Number::Integer(-1)), Ok(big("9223372036854775808")));
+    }
+
+    #[test]
+    fn big_demotes() {
+        assert_eq!(big("9223372036854775808").sub(Number::Integer(1)), Number::Integer(i64::MAX));
+        assert_eq!(big("18446744073709551616").div(big("4294967296")), Ok(Number::Integer(4294967296)));
+    }
+
+    #[test]
+    fn big_mixed() {
+        assert_eq!(big("18446744073709551616").add(Number::Float(0.5)), Number::Float(18446744073709551616.5));
+        assert_eq!(big("18446744073709551617").div(Number::Float(2.0)), Ok(Number::Float(9223372036854775808.5)));
+        assert_eq!(big("18446744073709551616").compare(&Number::Integer(i64::MAX)), Some(Ordering::Greater));
+        assert_eq!(big("-18446744073709551616").compare(&Number::Float(0.0)), Some(Ordering::Less));
+    }
+
+    fn ratio(s: &str) -> Number {
+        Number::Rational(Rational::parse(s).unwrap())
+    }
+
+    #[test]
+    fn rational_arithmetic() {
+        assert_eq!(Number::Integer(1).div(Number::Integer(3)), Ok(ratio("1/3")));
+        assert_eq!(Number::Integer(6).div(Number::Integer(3)), Ok(Number::Integer(2)));
+        assert_eq!(ratio("1/2").add(ratio("1/3")), ratio("5/6"));
+        assert_eq!(ratio("1/2").add(ratio("1/2")), Number::Integer(1));
+        assert_eq!(ratio("1/3").mul(Number::Integer(3)), Number::Integer(1));
+        assert_eq!(Number::Integer(1).sub(ratio("1/4")), ratio("3/4"));
+        assert_eq!(ratio("1/2").div(ratio("1/4")), Ok(Number::Integer(2)));
+        assert_eq!(big("18446744073709551616").div(big("36893488147419103232")), Ok(ratio("1/2")));
+    }
+
+    #[test]
+    fn rational_mixed() {
+        assert_eq!(ratio("1/2").add(Number::Float(0.5)), Number::Float(1.0));
+        assert_eq!(ratio("1/4").compare(&Number::Float(0.25)), Some(Ordering::Equal));
+        assert_eq!(ratio("1/3").compare(&ratio("1/2")), Some(Ordering::Less));
+        assert_eq!(ratio("7/2").compare(&Number::Integer(3)), Some(Ordering::Greater));
+    }
+
+    #[test]
+    fn compare() {
+        assert_eq!(Number::Integer(1).compare(&Number::Integer(2)), Some(Ordering::Less));
+        assert_eq!(Number::Integer(2).compare(&Number::Float(2.0)), Some(Ordering::Equal));
+        assert_eq!(Number::Float(2.5).compare(&Number::Integer(2)), Some(Ordering::Greater));
+        assert_eq!(Number::Float(f64::NAN).compare(&Number::Integer(2)), None);
+    }
+
+    #[test]
+    fn divide_by_zero() {
+        assert_eq!(Number::Integer(1).div(Number::Integer(0)), Err(EvalError::DivideByZero));
+        assert_eq!(Number::Float(1.0).div(Number::Float(0.0)), Ok(Number::Float(f64::INFINITY)));
+    }
+
+    #[test]
+    fn not_a_number() {
+        match Number::from_value(&Value::Str("1".to_string())) {
+            Err(EvalError::TypeError(_)) => (),
+            other                        => panic!("expected a type error, got {:?}", other)
+        }
+    }
+}
@@ -0,0 +1,388 @@
+use eval::EvalError;
+use parser::Sexpr;
+
+use std::collections::HashMap;
+
+const ELLIPSIS: &str = "...";
+
+/* a syntax-rules transformer. expansion is not hygienic: names in a template mean whatever
+   they mean where the macro is used */
+#[derive(Debug)]
+pub struct Macro {
+    pub name: String,
+    literals: Vec<String>,
+    rules:    Vec<(Sexpr, Sexpr)>
+}
+
+/* what a pattern variable matched, nested once for each ellipsis it sits under */
+#[derive(Debug, Clone)]
+enum Binding {
+    One(Sexpr),
+    Many(Vec<Bindings>)
+}
+
+type Bindings = HashMap<String, Binding>;
+
+impl Macro {
+    /* build from the arguments of (syntax-rules (literal ...) (pattern template) ...) */
+    pub fn new(name: &str, spec: &Sexpr) -> Result<Macro, EvalError> {
+        let args = match *spec {
+            Sexpr::List(ref list) => match list.split_first() {
+                Some((Sexpr::Symbol(head), args)) if head == "syntax-rules" => args,
+                _ => return Err(bad_syntax("define-syntax: expected a syntax-rules form"))
+            },
+            _ => return Err(bad_syntax("define-syntax: expected a syntax-rules form"))
+        };
+
+        let (literals, rules) = match args.split_first() {
+            Some((Sexpr::List(literals), rules)) => (literals, rules),
+            _ => return Err(bad_syntax("syntax-rules: expected a list of literals"))
+        };
+
+        let mut names = vec![];
+        for literal in literals {
+            match *literal {
+                Sexpr::Symbol(ref name) => names.push(name.clone()),
+                _                       => return Err(bad_syntax("syntax-rules: literals must be symbols"))
+            }
+        }
+
+        let mut pairs = vec![];
+        for rule in rules {
+            match *rule {
+                /* the pattern needs at least the keyword position, expand skips over it */
+                Sexpr::List(ref rule) if rule.len() == 2 => match rule[0] {
+                    Sexpr::List(ref pattern) if pattern.is_empty() => {
+                        return Err(bad_syntax("syntax-rules: a pattern must start with the keyword"))
+                    },
+                    Sexpr::List(_) | Sexpr::DottedList(..) => pairs.push((rule[0].clone(), rule[1].clone())),
+                    _ => return Err(bad_syntax("syntax-rules: a pattern must be a list"))
+                },
+                _ => return Err(bad_syntax("syntax-rules: rules must be (pattern template)"))
+            }
+        }
+
+        Ok(Macro { name: name.to_string(), literals: names, rules: pairs })
+    }
+
+    /* rewrite a use of the macro with the template of the first rule whose pattern matches */
+    pub fn expand(&self, form: &[Sexpr]) -> Result<Sexpr, EvalError> {
+        for (pattern, template) in &self.rules {
+            /* the keyword position is ignored */
+            let mut bindings = HashMap::new();
+            let matched = match *pattern {
+                Sexpr::List(ref pattern)                 => self.match_list(&pattern[1..], None, &form[1..], &mut bindings),
+                Sexpr::DottedList(ref pattern, ref tail) => self.match_list(&pattern[1..], Some(tail), &form[1..], &mut bindings),
+                _                                        => false
+            };
+            if matched {
+                return instantiate(template, &bindings)
+            }
+        }
+        Err(bad_syntax(&format!("{}: no syntax rule matches {:?}", self.name, form)))
+    }
+
+    fn match_pattern(&self, pattern: &Sexpr, form: &Sexpr, bindings: &mut Bindings) -> bool {
+        match (pattern, form) {
+            (Sexpr::Symbol(name), _) => {
+                if name == "_" {
+                    true
+                } else if self.literals.contains(name) {
+                    *form == *pattern
+                } else {
+                    bindings.insert(name.clone(), Binding::One(form.clone()));
+                    true
+                }
+            },
+            (Sexpr::List(pattern), Sexpr::List(form)) => self.match_list(pattern, None, form, bindings),
+            (Sexpr::DottedList(pattern, tail), Sexpr::List(form)) => self.match_list(pattern, Some(tail), form, bindings),
+            (Sexpr::DottedList(pattern, tail), Sexpr::DottedList(form, rest)) => {
+                pattern.len() <= form.len()
+                    && pattern.iter().zip(form).all(|(pattern, form)| self.match_pattern(pattern, form, bindings))
+                    && self.match_pattern(tail, &dotted(&form[pattern.len()..], rest), bindings)
+            },
+            _ => pattern == form
+        }
+    }
+
+    /* match a list of patterns, which may contain one `p ...`, and an optional dotted tail
+       pattern that takes whatever items are left over */
+    fn match_list(&self, patterns: &[Sexpr], tail: Option<&Sexpr>, forms: &[Sexpr], bindings: &mut Bindings) -> bool {
+        let ellipsis = patterns.iter().position(is_ellipsis);
+        let (before, repeated, after) = match ellipsis {
+            Some(i) if i > 0 => (&patterns[..i - 1], Some(&patterns[i - 1]), &patterns[i + 1..]),
+            _                => (patterns, None, &patterns[patterns.len()..])
+        };
+
+        let fixed = before.len() + after.len();
+        if forms.len() < fixed || (repeated.is_none() && tail.is_none() && forms.len() != fixed) {
+            return false
+        }
+
+        for (pattern, form) in before.iter().zip(forms) {
+            if !self.match_pattern(pattern, form, bindings) {
+                return false
+            }
+        }
+
+        let rest = &forms[before.len()..];
+        match repeated {
+            Some(repeated) => {
+                let count = rest.len() - after.len();
+                let mut matches = vec![];
+                for form in &rest[..count] {
+                    let mut inner = HashMap::new();
+                    if !self.match_pattern(repeated, form, &mut inner) {
+                        return false
+                    }
+                    matches.push(inner);
+                }
+                for name in pattern_variables(repeated, &self.literals) {
+                    bindings.insert(name, Binding::Many(matches.clone()));
+                }
+
+                for (pattern, form) in after.iter().zip(&rest[count..]) {
+                    if !self.match_pattern(pattern, form, bindings) {
+                        return false
+                    }
+                }
+                match tail {
+                    Some(tail) => self.match_pattern(tail, &Sexpr::List(vec![]), bindings),
+                    None       => true
+                }
+            },
+            None => match tail {
+                Some(tail) => self.match_pattern(tail, &Sexpr::List(rest.to_vec()), bindings),
+                None       => true
+            }
+        }
+    }
+}
+
+fn is_ellipsis(sexpr: &Sexpr) -> bool {
+    *sexpr == Sexpr::Symbol(ELLIPSIS.to_string())
+}
+
+fn dotted(items: &[Sexpr], tail: &Sexpr) -> Sexpr {
+    if items.is_empty() {
+        tail.clone()
+    } else {
+        Sexpr::DottedList(items.to_vec(), Box::new(tail.clone()))
+    }
+}
+
+fn bad_syntax(message: &str) -> EvalError {
+    EvalError::BadSyntax(message.to_string())
+}
+
+/* the names a pattern binds */
+fn pattern_variables(pattern: &Sexpr, literals: &[String]) -> Vec<String> {
+    match *pattern {
+        Sexpr::Symbol(ref name) if name != "_" && name != ELLIPSIS && !literals.contains(name) => vec![name.clone()],
+        Sexpr::List(ref items) => items.iter().flat_map(|item| pattern_variables(item, literals)).collect(),
+        Sexpr::DottedList(ref items, ref tail) => {
+            let mut names: Vec<String> = items.iter().flat_map(|item| pattern_variables(item, literals)).collect();
+            names.extend(pattern_variables(tail, literals));
+            names
+        },
+        _ => vec![]
+    }
+}
+
+/* fill in a template with what the pattern matched */
+fn instantiate(template: &Sexpr, bindings: &Bindings) -> Result<Sexpr, EvalError> {
+    match *template {
+        Sexpr::Symbol(ref name) => match bindings.get(name) {
+            Some(Binding::One(value)) => Ok(value.clone()),
+            Some(Binding::Many(_))    => Err(bad_syntax(&format!("{} must be followed by ... in the template", name))),
+            None                      => Ok(template.clone())
+        },
+        /* (... ...) stands for a literal ellipsis */
+        Sexpr::List(ref items) if items.len() == 2 && is_ellipsis(&items[0]) => Ok(items[1].clone()),
+        Sexpr::List(ref items) => Ok(Sexpr::List(instantiate_items(items, bindings)?)),
+        Sexpr::DottedList(ref items, ref tail) => {
+            let items = instantiate_items(items, bindings)?;
+            match instantiate(tail, bindings)? {
+                Sexpr::List(rest)             => Ok(Sexpr::List(items.into_iter().chain(rest).collect())),
+                Sexpr::DottedList(rest, tail) => Ok(Sexpr::DottedList(items.into_iter().chain(rest).collect(), tail)),
+                tail                          => Ok(Sexpr::DottedList(items, Box::new(tail)))
+            }
+        },
+        _ => Ok(template.clone())
+    }
+}
+
+/* instantiate each item, repeating any item followed by ... once per match */
+fn instantiate_items(items: &[Sexpr], bindings: &Bindings) -> Result<Vec<Sexpr>, EvalError> {
+    let mut result = vec![];
+    let mut i      = 0;
+    while i < items.len() {
+        let item = &items[i];
+        if items.get(i + 1).is_some_and(is_ellipsis) {
+            result.extend(repeat(item, bindings)?);
+            i += 2;
+        } else {
+            result.push(instantiate(item, bindings)?);
+            i += 1;
+        }
+    }
+    Ok(result)
+}
+
+/* the sub-template is instantiated once for each match of the ellipsis variables it uses */
+fn repeat(template: &Sexpr, bindings: &Bindings) -> Result<Vec<Sexpr>, EvalError> {
+    let names = template_symbols(template).into_iter()
+        .filter(|name| matches!(bindings.get(name), Some(Binding::Many(_))))
+        .collect::<Vec<_>>();
+
+    let mut count = None;
+    for name in &names {
+        if let Some(Binding::Many(matches)) = bindings.get(name) {
+            match count {
+                Some(n) if n != matches.len() => return Err(bad_syntax("ellipsis variables matched different lengths")),
+                _                             => count = Some(matches.len())
+            }
+        }
+    }
+    let count = match count {
+        Some(count) => count,
+        None        => return Err(bad_syntax("no pattern variable to repeat before ..."))
+    };
+
+    let mut results = vec![];
+    for i in 0..count {
+        let mut inner = bindings.clone();
+        for name in &names {
+            if let Some(Binding::Many(matches)) = bindings.get(name) {
+                inner.remove(name);
+                inner.extend(matches[i].clone());
+            }
+        }
+        results.push(instantiate(template, &inner)?);
+    }
+    Ok(results)
+}
+
+fn template_symbols(template: &Sexpr) -> Vec<String> {
+    match *template {
+        Sexpr::Symbol(ref name)                => vec![name.clone()],
+        Sexpr::List(ref items)                 => items.iter().flat_map(template_symbols).collect(),
+        Sexpr::DottedList(ref items, ref tail) => {
+            let mut names: Vec<String> = items.iter().flat_map(template_symbols).collect();
+            names.extend(template_symbols(tail));
+            names
+        },
+        _                                      => vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eval::eval;
+    use eval::Environment;
+    use eval::EvalError;
+    use eval::Value;
+    use parser::Parser;
+    use lexer::StringLexer;
+
+    /* evaluate every datum in `source`, returning the last value */
+    fn run(source: &str) -> Result<Value, EvalError> {
+        let mut env    = Environment::global();
+        let mut parser = Parser::new(StringLexer::new(source.to_string()));
+        let mut result = Ok(Value::Nil);
+        while let Ok(datum) = parser.parse() {
+            result = eval(&datum, &mut env);
+        }
+        result
+    }
+
+    fn int_list(values: &[i64]) -> Value {
+        Value::list(values.iter().map(|&n| Value::Integer(n)).collect())
+    }
+
+    #[test]
+    fn simple_macro() {
+        let source = "(define-syntax my-if (syntax-rules () ((_ c t e) (cond (c t) (else e)))))
+                      (list (my-if #t 1 2) (my-if #f 1 2))";
+        assert_eq!(run(source), Ok(int_list(&[1, 2])));
+    }
+
+    #[test]
+    fn arguments_are_not_evaluated() {
+        let source = "(define-syntax my-if (syntax-rules () ((_ c t e) (cond (c t) (else e)))))
+                      (my-if #t 1 (car '()))";
+        assert_eq!(run(source), Ok(Value::Integer(1)));
+    }
+
+    #[test]
+    fn ellipsis() {
+        let source = "(define-syntax my-list (syntax-rules () ((_ x ...) (list x ...))))
+                      (list (my-list 1 (+ 1 1) 3) (my-list))";
+        assert_eq!(run(source), Ok(Value::list(vec![int_list(&[1, 2, 3]), Value::Nil])));
+    }
+
+    #[test]
+    fn nested_ellipsis() {
+        let source = "(define-syntax my-let (syntax-rules () ((_ ((name value) ...) body ...) ((lambda (name ...) body ...) value ...))))
+                      (my-let ((a 1) (b 2)) (define c 3) (list a b c))";
+        assert_eq!(run(source), Ok(int_list(&[1, 2, 3])));
+
+        let source = "(define-syntax pairs (syntax-rules () ((_ (a b ...) ...) '((b ... a) ...))))
+                      (pairs (1 2 3) (4) (5 6))";
+        assert_eq!(run(source), Ok(Value::list(vec![int_list(&[2, 3, 1]), int_list(&[4]), int_list(&[6, 5])])));
+    }
+
+    #[test]
+    fn swap() {
+        let source = "(define-syntax swap! (syntax-rules () ((_ a b) (let ((tmp a)) (set! a b) (set! b tmp)))))
+                      (define x 1)
+                      (define y 2)
+                      (swap! x y)
+                      (list x y)";
+        assert_eq!(run(source), Ok(int_list(&[2, 1])));
+    }
+
+    #[test]
+    fn literals_and_multiple_rules() {
+        let source = "(define-syntax my-cond (syntax-rules (else)
+                        ((_ (else e)) e)
+                        ((_ (c e) rest ...) (if c e (my-cond rest ...)))))
+                      (list (my-cond (#f 1) ((= 1 1) 2) (else 3)) (my-cond (#f 1) (else 3)))";
+        assert_eq!(run(source), Ok(int_list(&[2, 3])));
+    }
+
+    #[test]
+    fn dotted_pattern() {
+        let source = "(define-syntax first (syntax-rules () ((_ a . rest) 'a)))
+                      (first x y z)";
+        assert_eq!(run(source), Ok(Value::Symbol("x".to_string())));
+    }
+
+    #[test]
+    fn recursive_macro_in_tail_position() {
+        let source = "(define-syntax my-or (syntax-rules () ((_) #f) ((_ e) e) ((_ e r ...) (let ((t e)) (if t t (my-or r ...))))))
+                      (define (count n) (my-or (= n 0) (count (- n 1))))
+                      (count 100000)";
+        assert_eq!(run(source), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn no_matching_rule() {
+        let source = "(define-syntax two (syntax-rules () ((_ a b) a)))
+                      (two 1)";
+        match run(source) {
+            Err(EvalError::BadSyntax(_)) => (),
+            other                        => panic!("expected bad syntax, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn bad_definitions() {
+        assert!(run("(define-syntax m (lambda (x) x))").is_err());
+        assert!(run("(define-syntax m (syntax-rules (1) ((_) 1)))").is_err());
+        assert!(run("(define-syntax m (syntax-rules () (_ 1)))").is_err());
+        assert_eq!(run("(define-syntax m (syntax-rules () (() 1)))"),
+                   Err(EvalError::BadSyntax("syntax-rules: a pattern must start with the keyword".to_string())));
+        assert!(run("(define-syntax m (syntax-rules () ((_ x) (x ...))))  (m 1)").is_err());
+    }
+}
@@ -0,0 +1,694 @@
+use bigint::BigInt;
+use eval::Environment;
+use eval::EvalError;
+use eval::Macro;
+use lexer::write_char;
+use parser::Sexpr;
+use rational::Rational;
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ptr;
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub enum Value {
+    Integer(i64),
+    BigInt(BigInt),
+    Rational(Rational),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+    Char(char),
+    Symbol(String),
+    Nil,
+    Pair(Rc<RefCell<(Value, Value)>>),
+    Vector(Rc<RefCell<Vec<Value>>>),
+    Procedure(Rc<Lambda>),
+    Primitive(Primitive),
+    NativeProcedure(Native),
+    Macro(Rc<Macro>),
+    Condition(Rc<EvalError>),
+    Continuation(Rc<Continuation>),
+    Values(Vec<Value>)
+}
+
+impl Value {
+    /* pairs are shared and mutable like vectors, `set-car!` and `set-cdr!` change every copy */
+    pub fn cons(car: Value, cdr: Value) -> Value {
+        Value::Pair(Rc::new(RefCell::new((car, cdr))))
+    }
+
+    /* a proper list, i.e. pairs ending in Nil */
+    pub fn list(values: Vec<Value>) -> Value {
+        values.into_iter().rev().fold(Value::Nil, |cdr, car| Value::cons(car, cdr))
+    }
+
+    /* vectors are shared and mutable, copies of the value see `vector-set!`s made through any of them */
+    pub fn vector(values: Vec<Value>) -> Value {
+        Value::Vector(Rc::new(RefCell::new(values)))
+    }
+
+    /* what `(values ...)` returns: a single value stands for itself, any other number are kept
+       together for call-with-values or let-values to take apart */
+    pub fn values(mut values: Vec<Value>) -> Value {
+        if values.len() == 1 {
+            values.pop().unwrap()
+        } else {
+            Value::Values(values)
+        }
+    }
+
+    /* the reverse of `values` */
+    pub fn into_values(self) -> Vec<Value> {
+        match self {
+            Value::Values(values) => values,
+            value                 => vec![value]
+        }
+    }
+
+    /* convert quoted syntax into data */
+    pub fn from_datum(datum: &Sexpr) -> Value {
+        match *datum {
+            Sexpr::Integer(n)      => Value::Integer(n),
+            Sexpr::BigInt(ref n)   => Value::BigInt(n.clone()),
+            Sexpr::Rational(ref n) => Value::Rational(n.clone()),
+            Sexpr::Float(n)        => Value::Float(n),
+            Sexpr::Str(ref s)      => Value::Str(s.clone()),
+            Sexpr::Bool(b)         => Value::Bool(b),
+            Sexpr::Char(c)         => Value::Char(c),
+            Sexpr::Symbol(ref s)   => Value::Symbol(s.clone()),
+            Sexpr::List(ref l)     => Value::list(l.iter().map(Value::from_datum).collect()),
+            Sexpr::Vector(ref v)   => Value::vector(v.iter().map(Value::from_datum).collect()),
+            Sexpr::DottedList(ref l, ref tail) => {
+                l.iter().rev().fold(Value::from_datum(tail), |cdr, car| Value::cons(Value::from_datum(car), cdr))
+            }
+        }
+    }
+
+    /* the elements of a proper list, None for anything else, including a list that `set-cdr!`
+       has made circular */
+    pub fn list_to_vec(&self) -> Option<Vec<Value>> {
+        let mut items = vec![];
+        let mut rest  = self.clone();
+        /* trails behind at half the speed, a circular list eventually brings `rest` back round to it */
+        let mut slow  = self.clone();
+        loop {
+            rest = match rest {
+                Value::Pair(ref pair) => {
+                    let pair = pair.borrow();
+                    items.push(pair.0.clone());
+                    pair.1.clone()
+                },
+                Value::Nil            => return Some(items),
+                _                     => return None
+            };
+            if items.len() % 2 == 0 {
+                slow = match slow {
+                    Value::Pair(ref pair) => pair.borrow().1.clone(),
+                    _                     => unreachable!()
+                };
+                if let (Value::Pair(a), Value::Pair(b)) = (&rest, &slow) {
+                    if Rc::ptr_eq(a, b) {
+                        return None
+                    }
+                }
+            }
+        }
+    }
+
+    /* only #f is false, everything else (including 0 and '()) is true */
+    pub fn is_true(&self) -> bool {
+        *self != Value::Bool(false)
+    }
+}
+
+/* structural equality, as `equal?` sees it. pairs and vectors can contain themselves, so a pair of
+   them already being compared further up is taken to be equal rather than compared again */
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        equal(self, other, &mut HashSet::new())
+    }
+}
+
+fn equal(a: &Value, b: &Value, comparing: &mut HashSet<(usize, usize)>) -> bool {
+    match (a, b) {
+        (Value::Pair(x), Value::Pair(y))                       => {
+            if Rc::ptr_eq(x, y) || !comparing.insert((Rc::as_ptr(x) as usize, Rc::as_ptr(y) as usize)) {
+                return true
+            }
+            let (x, y) = (x.borrow(), y.borrow());
+            equal(&x.0, &y.0, comparing) && equal(&x.1, &y.1, comparing)
+        },
+        (Value::Vector(x), Value::Vector(y))                   => {
+            if Rc::ptr_eq(x, y) || !comparing.insert((Rc::as_ptr(x) as usize, Rc::as_ptr(y) as usize)) {
+                return true
+            }
+            all_equal(&x.borrow(), &y.borrow(), comparing)
+        },
+        (Value::Values(x), Value::Values(y))                   => all_equal(x, y, comparing),
+        (Value::Integer(x), Value::Integer(y))                 => x == y,
+        (Value::BigInt(x), Value::BigInt(y))                   => x == y,
+        (Value::Rational(x), Value::Rational(y))               => x == y,
+        (Value::Float(x), Value::Float(y))                     => x == y,
+        (Value::Str(x), Value::Str(y))                         => x == y,
+        (Value::Bool(x), Value::Bool(y))                       => x == y,
+        (Value::Char(x), Value::Char(y))                       => x == y,
+        (Value::Symbol(x), Value::Symbol(y))                   => x == y,
+        (Value::Nil, Value::Nil)                               => true,
+        (Value::Procedure(x), Value::Procedure(y))             => x == y,
+        (Value::Primitive(x), Value::Primitive(y))             => x == y,
+        (Value::NativeProcedure(x), Value::NativeProcedure(y)) => x == y,
+        (Value::Macro(x), Value::Macro(y))                     => x == y,
+        (Value::Condition(x), Value::Condition(y))             => x == y,
+        (Value::Continuation(x), Value::Continuation(y))       => x == y,
+        _                                                      => false
+    }
+}
+
+fn all_equal(xs: &[Value], ys: &[Value], comparing: &mut HashSet<(usize, usize)>) -> bool {
+    xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| equal(x, y, comparing))
+}
+
+/* as derived, except that pairs and vectors show their contents in Scheme notation, which copes with
+   them containing themselves */
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Integer(n)             => f.debug_tuple("Integer").field(&n).finish(),
+            Value::BigInt(ref n)          => f.debug_tuple("BigInt").field(n).finish(),
+            Value::Rational(ref n)        => f.debug_tuple("Rational").field(n).finish(),
+            Value::Float(n)               => f.debug_tuple("Float").field(&n).finish(),
+            Value::Str(ref s)             => f.debug_tuple("Str").field(s).finish(),
+            Value::Bool(b)                => f.debug_tuple("Bool").field(&b).finish(),
+            Value::Char(c)                => f.debug_tuple("Char").field(&c).finish(),
+            Value::Symbol(ref s)          => f.debug_tuple("Symbol").field(s).finish(),
+            Value::Nil                    => write!(f, "Nil"),
+            Value::Pair(_)                => write!(f, "Pair({})", self.write()),
+            Value::Vector(_)              => write!(f, "Vector({})", self.write()),
+            Value::Procedure(ref p)       => f.debug_tuple("Procedure").field(p).finish(),
+            Value::Primitive(ref p)       => f.debug_tuple("Primitive").field(p).finish(),
+            Value::NativeProcedure(ref p) => f.debug_tuple("NativeProcedure").field(p).finish(),
+            Value::Macro(ref m)           => f.debug_tuple("Macro").field(m).finish(),
+            Value::Condition(ref err)     => f.debug_tuple("Condition").field(err).finish(),
+            Value::Continuation(ref k)    => f.debug_tuple("Continuation").field(k).finish(),
+            Value::Values(ref values)     => f.debug_tuple("Values").field(values).finish()
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Value {
+        Value::Integer(n)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Value {
+        Value::Float(n)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::Str(s)
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(s: &'a str) -> Value {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl From<char> for Value {
+    fn from(c: char) -> Value {
+        Value::Char(c)
+    }
+}
+
+impl TryFrom<Value> for i64 {
+    type Error = EvalError;
+
+    fn try_from(value: Value) -> Result<i64, EvalError> {
+        match value {
+            Value::Integer(n) => Ok(n),
+            other             => Err(EvalError::TypeError(format!("expected an integer, got {:?}", other)))
+        }
+    }
+}
+
+/* integers promote, as they do in arithmetic */
+impl TryFrom<Value> for f64 {
+    type Error = EvalError;
+
+    fn try_from(value: Value) -> Result<f64, EvalError> {
+        match value {
+            Value::Float(n)    => Ok(n),
+            Value::Integer(n)  => Ok(n as f64),
+            Value::BigInt(n)   => Ok(n.to_f64()),
+            Value::Rational(n) => Ok(n.to_f64()),
+            other              => Err(EvalError::TypeError(format!("expected a number, got {:?}", other)))
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = EvalError;
+
+    fn try_from(value: Value) -> Result<String, EvalError> {
+        match value {
+            Value::Str(s) => Ok(s),
+            other         => Err(EvalError::TypeError(format!("expected a string, got {:?}", other)))
+        }
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = EvalError;
+
+    fn try_from(value: Value) -> Result<bool, EvalError> {
+        match value {
+            Value::Bool(b) => Ok(b),
+            other          => Err(EvalError::TypeError(format!("expected a boolean, got {:?}", other)))
+        }
+    }
+}
+
+impl TryFrom<Value> for char {
+    type Error = EvalError;
+
+    fn try_from(value: Value) -> Result<char, EvalError> {
+        match value {
+            Value::Char(c) => Ok(c),
+            other          => Err(EvalError::TypeError(format!("expected a character, got {:?}", other)))
+        }
+    }
+}
+
+/* formats a value the way `display` or, when `quote` is set, `write` would print it */
+pub struct Printer<'a> {
+    value:     &'a Value,
+    quote:     bool,
+    enclosing: Option<&'a Enclosing<'a>>
+}
+
+/* the pairs and vectors a printer is inside of, innermost first. one that contains itself is
+   printed as `...` where it comes round again */
+struct Enclosing<'a> {
+    ptr:   usize,
+    outer: Option<&'a Enclosing<'a>>
+}
+
+impl<'a> Enclosing<'a> {
+    fn contains(&self, ptr: usize) -> bool {
+        self.ptr == ptr || self.outer.is_some_and(|outer| outer.contains(ptr))
+    }
+}
+
+impl Value {
+    /* human readable form, strings are printed without quotes or escapes */
+    pub fn display(&self) -> Printer<'_> {
+        Printer { value: self, quote: false, enclosing: None }
+    }
+
+    /* re-readable form, strings are quoted and escaped */
+    pub fn write(&self) -> Printer<'_> {
+        Printer { value: self, quote: true, enclosing: None }
+    }
+}
+
+/* the REPL echoes results in their re-readable form */
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write().fmt(f)
+    }
+}
+
+impl<'a> Printer<'a> {
+    fn nested<'b>(&self, value: &'b Value, enclosing: &'b Enclosing<'b>) -> Printer<'b> {
+        Printer { value, quote: self.quote, enclosing: Some(enclosing) }
+    }
+
+    /* whether the pair or vector at `ptr` is already being printed further out */
+    fn inside(&self, ptr: usize) -> bool {
+        self.enclosing.is_some_and(|enclosing| enclosing.contains(ptr))
+    }
+
+    fn enclose(&self, ptr: usize) -> Enclosing<'_> {
+        Enclosing { ptr, outer: self.enclosing }
+    }
+}
+
+impl<'a> fmt::Display for Printer<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self.value {
+            Value::Integer(n)               => write!(f, "{}", n),
+            Value::BigInt(ref n)            => write!(f, "{}", n),
+            Value::Rational(ref n)          => write!(f, "{}", n),
+            Value::Float(n)                 => write_float(f, n),
+            Value::Str(ref s) if self.quote => write_string(f, s),
+            Value::Str(ref s)               => write!(f, "{}", s),
+            Value::Bool(b)                  => write!(f, "{}", if b { "#t" } else { "#f" }),
+            Value::Char(c) if self.quote    => write_char(f, c),
+            Value::Char(c)                  => write!(f, "{}", c),
+            Value::Symbol(ref s)            => write!(f, "{}", s),
+            Value::Nil                      => write!(f, "()"),
+            Value::Pair(ref pair) if self.inside(Rc::as_ptr(pair) as usize) => write!(f, "..."),
+            Value::Pair(ref pair)           => {
+                /* the pairs of the list so far, so a cdr leading back into it ends the list with `...` */
+                let mut seen = HashSet::new();
+                let mut last = 0;
+                let mut rest = Value::Pair(pair.clone());
+                write!(f, "(")?;
+                loop {
+                    rest = match rest {
+                        Value::Pair(ref pair) => {
+                            let ptr = Rc::as_ptr(pair) as usize;
+                            if !seen.is_empty() && (self.inside(ptr) || seen.contains(&ptr)) {
+                                write!(f, " ...")?;
+                                break
+                            }
+                            seen.insert(ptr);
+                            last = ptr;
+                            let pair = pair.borrow();
+                            let here = self.enclose(ptr);
+                            write!(f, "{}{}", if seen.len() == 1 { "" } else { " " }, self.nested(&pair.0, &here))?;
+                            pair.1.clone()
+                        },
+                        Value::Nil            => break,
+                        ref tail              => {
+                            let here = self.enclose(last);
+                            write!(f, " . {}", self.nested(tail, &here))?;
+                            break
+                        }
+                    }
+                }
+                write!(f, ")")
+            },
+            Value::Vector(ref items) if self.inside(Rc::as_ptr(items) as usize) => write!(f, "..."),
+            Value::Vector(ref items)        => {
+                let here = self.enclose(Rc::as_ptr(items) as usize);
+                write!(f, "#(")?;
+                for (i, item) in items.borrow().iter().enumerate() {
+                    write!(f, "{}{}", if i == 0 { "" } else { " " }, self.nested(item, &here))?;
+                }
+                write!(f, ")")
+            },
+            Value::Procedure(_)             => write!(f, "#<procedure>"),
+            Value::Primitive(ref p)         => write!(f, "#<procedure {}>", p.name),
+            Value::NativeProcedure(ref p)   => write!(f, "#<procedure {}>", p.name),
+            Value::Macro(ref m)             => write!(f, "#<macro {}>", m.name),
+            Value::Condition(ref err)       => write!(f, "#<condition {}>", err),
+            Value::Continuation(_)          => write!(f, "#<continuation>"),
+            Value::Values(ref values)       => {
+                for (i, value) in values.iter().enumerate() {
+                    let printer = Printer { value, quote: self.quote, enclosing: self.enclosing };
+                    write!(f, "{}{}", if i == 0 { "" } else { " " }, printer)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/* always keep a decimal point or exponent so floats read back as floats */
+fn write_float(f: &mut fmt::Formatter, n: f64) -> fmt::Result {
+    if n.is_nan() {
+        write!(f, "+nan.0")
+    } else if n.is_infinite() {
+        write!(f, "{}inf.0", if n > 0.0 { "+" } else { "-" })
+    } else {
+        write!(f, "{:?}", n)
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' | '\\' | '\n' => write!(f, "\\{}", c)?,
+            _                 => write!(f, "{}", c)?
+        }
+    }
+    write!(f, "\"")
+}
+
+/* a user-defined procedure, closing over the environment it was created in */
+pub struct Lambda {
+    pub params: Vec<String>,
+    pub rest:   Option<String>,
+    pub body:   Vec<Sexpr>,
+    pub env:    Environment
+}
+
+/* macros, like procedures, are only ever equal to themselves */
+impl PartialEq for Macro {
+    fn eq(&self, other: &Macro) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
+/* procedures are only ever equal to themselves */
+impl PartialEq for Lambda {
+    fn eq(&self, other: &Lambda) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
+/* the captured environment usually refers back to the procedure, so leave it out */
+impl fmt::Debug for Lambda {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Lambda")
+            .field("params", &self.params)
+            .field("rest", &self.rest)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+/* an escape-only continuation captured by call/cc. calling it unwinds the Rust stack with an
+   EvalError::Escape that only the call/cc which made it catches, so it can't be re-entered once
+   that has returned */
+#[derive(Debug)]
+pub struct Continuation;
+
+/* each call/cc makes a new continuation, equal only to itself */
+impl PartialEq for Continuation {
+    fn eq(&self, other: &Continuation) -> bool {
+        ptr::eq(self, other)
+    }
+}
+
+pub type PrimitiveFn = fn(&[Value], &mut Environment) -> Result<Value, EvalError>;
+
+pub type NativeFn = dyn Fn(&[Value]) -> Result<Value, EvalError>;
+
+/* a builtin procedure, called with its evaluated arguments and the caller's environment */
+#[derive(Clone, Copy)]
+pub struct Primitive {
+    pub name: &'static str,
+    pub func: PrimitiveFn
+}
+
+impl PartialEq for Primitive {
+    fn eq(&self, other: &Primitive) -> bool {
+        self.name == other.name
+    }
+}
+
+impl fmt::Debug for Primitive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Primitive({})", self.name)
+    }
+}
+
+/* a procedure supplied by the host application */
+#[derive(Clone)]
+pub struct Native {
+    pub name: String,
+    pub func: Rc<NativeFn>
+}
+
+impl PartialEq for Native {
+    fn eq(&self, other: &Native) -> bool {
+        Rc::ptr_eq(&self.func, &other.func)
+    }
+}
+
+impl fmt::Debug for Native {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Native({})", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eval::eval;
+    use parser::read_str;
+
+    fn lambda() -> Value {
+        Value::Procedure(Rc::new(Lambda { params: vec![], rest: None, body: vec![], env: Environment::new() }))
+    }
+
+    #[test]
+    fn pair_equality() {
+        let a = Value::cons(Value::Integer(1), Value::Nil);
+        let b = Value::cons(Value::Integer(1), Value::Nil);
+        assert_eq!(a, b);
+        assert!(a != Value::cons(Value::Integer(2), Value::Nil));
+    }
+
+    #[test]
+    fn list() {
+        let expected = Value::cons(Value::Integer(1), Value::cons(Value::Integer(2), Value::Nil));
+        assert_eq!(Value::list(vec![Value::Integer(1), Value::Integer(2)]), expected);
+        assert_eq!(Value::list(vec![]), Value::Nil);
+    }
+
+    #[test]
+    fn list_to_vec() {
+        let items = vec![Value::Integer(1), Value::Integer(2)];
+        assert_eq!(Value::list(items.clone()).list_to_vec(), Some(items));
+        assert_eq!(Value::Nil.list_to_vec(), Some(vec![]));
+        assert_eq!(Value::cons(Value::Integer(1), Value::Integer(2)).list_to_vec(), None);
+        assert_eq!(Value::Integer(1).list_to_vec(), None);
+    }
+
+    #[test]
+    fn from_datum() {
+        let datum = Sexpr::List(vec![Sexpr::Symbol("a".to_string()), Sexpr::List(vec![])]);
+        assert_eq!(Value::from_datum(&datum), Value::list(vec![Value::Symbol("a".to_string()), Value::Nil]));
+    }
+
+    #[test]
+    fn from_dotted_datum() {
+        let datum = Sexpr::DottedList(vec![Sexpr::Integer(1), Sexpr::Integer(2)], Box::new(Sexpr::Integer(3)));
+        assert_eq!(Value::from_datum(&datum), Value::cons(Value::Integer(1), Value::cons(Value::Integer(2), Value::Integer(3))));
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(i64::try_from(Value::from(42)), Ok(42));
+        assert_eq!(f64::try_from(Value::from(1.5)), Ok(1.5));
+        assert_eq!(String::try_from(Value::from("hi")), Ok("hi".to_string()));
+        assert_eq!(String::try_from(Value::from("hi".to_string())), Ok("hi".to_string()));
+        assert_eq!(bool::try_from(Value::from(true)), Ok(true));
+        assert_eq!(char::try_from(Value::from('λ')), Ok('λ'));
+    }
+
+    #[test]
+    fn float_from_integer() {
+        assert_eq!(f64::try_from(Value::Integer(2)), Ok(2.0));
+    }
+
+    #[test]
+    fn try_from_mismatch() {
+        match i64::try_from(Value::Str("1".to_string())) {
+            Err(EvalError::TypeError(_)) => (),
+            other                        => panic!("expected a type error, got {:?}", other)
+        }
+        assert!(i64::try_from(Value::Float(1.0)).is_err());
+        assert!(f64::try_from(Value::Nil).is_err());
+        assert!(String::try_from(Value::Symbol("a".to_string())).is_err());
+        assert!(bool::try_from(Value::Integer(0)).is_err());
+    }
+
+    #[test]
+    fn convert_eval_result() {
+        let mut env = Environment::global();
+        let result  = eval(&read_str("(* 6 7)").unwrap(), &mut env).unwrap();
+        assert_eq!(i64::try_from(result), Ok(42));
+    }
+
+    #[test]
+    fn display_and_write() {
+        let value = Value::list(vec![Value::from("a \"b\""), Value::Symbol("c".to_string()), Value::Float(2.0)]);
+        assert_eq!(value.display().to_string(), "(a \"b\" c 2.0)");
+        assert_eq!(value.write().to_string(), "(\"a \\\"b\\\"\" c 2.0)");
+        assert_eq!(Value::cons(Value::Integer(1), Value::Integer(2)).write().to_string(), "(1 . 2)");
+        assert_eq!(Value::Nil.write().to_string(), "()");
+        assert_eq!(Value::Bool(false).display().to_string(), "#f");
+    }
+
+    #[test]
+    fn display_list() {
+        let list = Value::list(vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)]);
+        assert_eq!(list.to_string(), "(1 2 3)");
+        assert_eq!(Value::list(vec![list, Value::Nil]).to_string(), "((1 2 3) ())");
+    }
+
+    #[test]
+    fn display_dotted() {
+        assert_eq!(Value::cons(Value::Integer(1), Value::Integer(2)).to_string(), "(1 . 2)");
+        let dotted = Value::cons(Value::Integer(1), Value::cons(Value::Integer(2), Value::Integer(3)));
+        assert_eq!(dotted.to_string(), "(1 2 . 3)");
+    }
+
+    #[test]
+    fn display_atoms() {
+        assert_eq!(Value::Float(1.0).to_string(), "1.0");
+        assert_eq!(Value::Float(-0.25).to_string(), "-0.25");
+        assert_eq!(Value::Float(1.0 / 0.0).to_string(), "+inf.0");
+        assert_eq!(Value::Integer(-7).to_string(), "-7");
+        assert_eq!(Value::Bool(true).to_string(), "#t");
+        assert_eq!(Value::Nil.to_string(), "()");
+        assert_eq!(Value::from("a\"b").to_string(), "\"a\\\"b\"");
+        assert_eq!(Value::Symbol("foo".to_string()).to_string(), "foo");
+    }
+
+    #[test]
+    fn display_cycles() {
+        let mut env = Environment::global();
+        let mut run = |source: &str| eval(&read_str(source).unwrap(), &mut env).unwrap();
+        run("(define p (list 1 2))");
+        run("(set-cdr! (cdr p) p)");
+        assert_eq!(run("p").to_string(), "(1 2 ...)");
+        run("(define q (list 1 2))");
+        run("(set-car! q q)");
+        assert_eq!(run("q").to_string(), "(... 2)");
+        run("(define v (vector 1 2))");
+        run("(vector-set! v 1 (list v))");
+        assert_eq!(run("v").to_string(), "#(1 (...))");
+        assert_eq!(run("(list p p)").to_string(), "((1 2 ...) (1 2 ...))");
+    }
+
+    #[test]
+    fn cycles_compare_and_are_not_lists() {
+        let mut env = Environment::global();
+        let mut run = |source: &str| eval(&read_str(source).unwrap(), &mut env).unwrap();
+        run("(define p (list 1 2))");
+        run("(set-cdr! (cdr p) p)");
+        run("(define q (list 1 2 1 2))");
+        run("(set-cdr! (cdr (cdr (cdr q))) q)");
+        assert_eq!(run("(equal? p p)"), Value::Bool(true));
+        assert_eq!(run("(equal? p q)"), Value::Bool(true));
+        assert_eq!(run("(equal? p (list 1 2))"), Value::Bool(false));
+        assert_eq!(run("p").list_to_vec(), None);
+        assert_eq!(run("(cdr p)").list_to_vec(), None);
+        assert_eq!(run("(list 1 2 3)").list_to_vec(), Some(vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)]));
+    }
+
+    #[test]
+    fn display_reads_back() {
+        let source = "(1 2.5 \"a \\\"q\\\"\" (b . c) #f)";
+        let value  = Value::from_datum(&read_str(source).unwrap());
+        assert_eq!(read_str(&value.to_string()), read_str(source));
+    }
+
+    #[test]
+    fn procedure_identity() {
+        let f = lambda();
+        assert_eq!(f, f.clone());
+        assert!(f != lambda());
+    }
+}
@@ -1,33 +1,129 @@
 use super::Lexer;
+use super::LexError;
+use super::LexerState;
+use std::char;
+use std::cmp;
+use std::io::ErrorKind;
 use std::io::Read;
-use std::io::BufReader;
+use std::str;
+
+const BUFFER_SIZE: usize = 4096;
+
+/* bytes of already-read input kept at the front of the buffer on refill, bounding how far `restore` can rewind */
+const REWIND_SIZE: usize = 1024;
 
 pub struct IOLexer<T: Read> {
-    input: BufReader<T>,
-    buf:   [u8; 1],
-    eof:   bool,
-    line:  u32,
-    chr:   u32
+    input:         T,
+    buf:           Box<[u8]>,
+    start:         usize,
+    pos:           usize,
+    len:           usize,
+    offset:        usize,
+    current:       char,
+    eof:           bool,
+    line:          u32,
+    chr:           u32,
+    skip_comments: bool,
+    fold_case:     bool
 }
 
 impl<T: Read> IOLexer<T> {
     pub fn new(input: T) -> IOLexer<T> {
-        let mut lexer = IOLexer { input: BufReader::new(input), buf: [0], eof: false, line: 1, chr: 1 };
+        let mut lexer = IOLexer {
+            input,
+            buf:           vec![0; BUFFER_SIZE].into_boxed_slice(),
+            start:         0,
+            pos:           0,
+            len:           0,
+            offset:        0,
+            current:       '\0',
+            eof:           false,
+            line:          1,
+            chr:           1,
+            skip_comments: false,
+            fold_case:     false
+        };
         lexer.read_char();
         lexer
     }
 
+    /* decode the next UTF-8 character from the buffer, invalid sequences become U+FFFD */
     fn read_char(&mut self) {
-        match self.input.read(&mut self.buf) {
-            Ok(0) | Err(_) => {
+        self.offset = self.start + self.pos;
+        let first = match self.read_byte() {
+            Some(b) => b,
+            None    => {
                 self.eof = true;
-            },
-            Ok(_) => ()
+                return
+            }
+        };
+
+        let width = match first {
+            0x00 ..= 0x7F => 1,
+            0xC0 ..= 0xDF => 2,
+            0xE0 ..= 0xEF => 3,
+            0xF0 ..= 0xF7 => 4,
+            _             => 0
+        };
+
+        let mut bytes = [first, 0, 0, 0];
+        let mut count = 1;
+        while count < width {
+            match self.peek_byte() {
+                Some(b) if b & 0xC0 == 0x80 => {
+                    bytes[count] = b;
+                    self.pos += 1;
+                    count += 1;
+                },
+                _ => break
+            }
+        }
+
+        self.current = match str::from_utf8(&bytes[..count]) {
+            Ok(s) if count == width => s.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER),
+            _                       => char::REPLACEMENT_CHARACTER
+        };
+    }
+
+    fn read_byte(&mut self) -> Option<u8> {
+        let byte = self.peek_byte();
+        if byte.is_some() {
+            self.pos += 1;
+        }
+        byte
+    }
+
+    fn peek_byte(&mut self) -> Option<u8> {
+        if self.pos < self.len || self.fill() {
+            Some(self.buf[self.pos])
+        } else {
+            None
+        }
+    }
+
+    /* refill the buffer from the underlying reader, returns false at end of input */
+    fn fill(&mut self) -> bool {
+        let keep = cmp::min(self.len, REWIND_SIZE);
+        self.buf.copy_within(self.len - keep..self.len, 0);
+        self.start += self.len - keep;
+        self.pos    = keep;
+        self.len    = keep;
+
+        loop {
+            match self.input.read(&mut self.buf[keep..]) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.len += n;
+                    return true
+                },
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => (),
+                Err(_) => return false
+            }
         }
     }
 }
 
-impl<T: Read> Lexer for IOLexer<T> {
+impl<'a, T: Read> Lexer<'a> for IOLexer<T> {
     fn get(&mut self) -> Option<char> {
         match self.peek() {
             None    => None,
@@ -43,15 +139,15 @@ impl<T: Read> Lexer for IOLexer<T> {
         if self.eof {
             None
         } else {
-            Some(self.buf[0] as char)
+            Some(self.current)
         }
     }
 
-    fn set_line(&mut self, line: u32) -> () {
+    fn set_line(&mut self, line: u32) {
         self.line = line
     }
 
-    fn set_chr(&mut self, chr: u32) -> () {
+    fn set_chr(&mut self, chr: u32) {
         self.chr = chr
     }
 
@@ -62,16 +158,53 @@ impl<T: Read> Lexer for IOLexer<T> {
     fn chr(&self) -> u32 {
         self.chr
     }
+
+    fn skip_comments(&self) -> bool {
+        self.skip_comments
+    }
+
+    fn set_skip_comments(&mut self, skip: bool) {
+        self.skip_comments = skip
+    }
+
+    fn fold_case(&self) -> bool {
+        self.fold_case
+    }
+
+    fn set_fold_case(&mut self, fold: bool) {
+        self.fold_case = fold
+    }
+
+    fn checkpoint(&self) -> LexerState {
+        LexerState { offset: self.offset, line: self.line, chr: self.chr }
+    }
+
+    /* the stream can't seek, so only checkpoints still held in the buffer can be restored;
+       at least the last REWIND_SIZE bytes are always available. older ones are a LexError::REWIND
+       and leave the lexer where it was */
+    fn restore(&mut self, state: LexerState) -> Result<(), LexError> {
+        if state.offset < self.start || state.offset > self.start + self.len {
+            return Err(LexError::REWIND(state.line, state.chr))
+        }
+
+        self.pos  = state.offset - self.start;
+        self.eof  = false;
+        self.line = state.line;
+        self.chr  = state.chr;
+        self.read_char();
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use lexer::Lexer;
+    use lexer::lexer::Token;
     use std::io::Read;
     use std::io::Result;
 
-    static TEST_STRING : &'static str = "ab\ncd";
+    static TEST_STRING : &str = "ab\ncd";
 
     struct FakeFile {
         cursor: usize,
@@ -80,7 +213,11 @@ mod tests {
 
     impl FakeFile {
         fn new() -> FakeFile {
-            FakeFile { cursor : 0, data: TEST_STRING.to_string().into_bytes() }
+            FakeFile::with_data(TEST_STRING.to_string())
+        }
+
+        fn with_data(data: String) -> FakeFile {
+            FakeFile { cursor : 0, data: data.into_bytes() }
         }
 
         fn at(&self, i: usize) -> u8 {
@@ -92,7 +229,7 @@ mod tests {
         }
 
         fn move_cursor(&mut self, by: usize) {
-            self.cursor = self.cursor + by;
+            self.cursor += by;
         }
 
         fn cursor(&self) -> usize {
@@ -108,7 +245,7 @@ mod tests {
                 let offset = self.cursor() + i;
                 if offset < self.len() {
                     buf[i] = self.at(offset);
-                    count = count + 1;
+                    count += 1;
                 } else {
                     break;
                 }
@@ -122,7 +259,7 @@ mod tests {
     #[test]
     fn new() {
         let lexer = IOLexer::new(FakeFile::new());
-        assert_eq!(lexer.eof, false);
+        assert!(!lexer.eof);
         assert_eq!(lexer.line, 1);
         assert_eq!(lexer.chr, 1);
     }
@@ -198,4 +335,98 @@ mod tests {
         lexer.chr = 43;
         assert_eq!(lexer.chr(), 43);
     }
+
+    #[test]
+    fn get_multibyte() {
+        let mut lexer = IOLexer::new(FakeFile::with_data("λé\n\u{1F600}".to_string()));
+        assert_eq!(Some('λ'), lexer.get());
+        assert_eq!(Some('é'), lexer.get());
+        assert_eq!(Some('\n'), lexer.get());
+        assert_eq!(lexer.line, 2);
+        assert_eq!(Some('\u{1F600}'), lexer.get());
+        assert_eq!(lexer.chr, 2);
+        assert_eq!(None, lexer.get());
+    }
+
+    #[test]
+    fn get_invalid_utf8() {
+        let data = FakeFile { cursor: 0, data: vec![b'a', 0xE2, 0x28, b'b', 0xFF] };
+        let mut lexer = IOLexer::new(data);
+        assert_eq!(Some('a'), lexer.get());
+        assert_eq!(Some(char::REPLACEMENT_CHARACTER), lexer.get());
+        assert_eq!(Some('('), lexer.get());
+        assert_eq!(Some('b'), lexer.get());
+        assert_eq!(Some(char::REPLACEMENT_CHARACTER), lexer.get());
+        assert_eq!(None, lexer.get());
+    }
+
+    #[test]
+    fn read_across_chunks() {
+        let line      = "( ident-λ \"stríng\" 12345 ) ; comment\n";
+        let lines     = 1000;
+        let mut lexer = IOLexer::new(FakeFile::with_data(line.repeat(lines)));
+        assert!(line.len() * lines > 8 * BUFFER_SIZE);
+
+        let mut tokens = vec![];
+        while let Ok(token) = lexer.next() {
+            tokens.push(token)
+        }
+
+        assert_eq!(tokens.len(), 6 * lines);
+        for (i, chunk) in tokens.chunks(6).enumerate() {
+            let n = i as u32 + 1;
+            assert_eq!(chunk, &[
+                Token::LPAR(n, 1),
+                Token::IDENT("ident-λ".into(), n, 3),
+                Token::STRING("stríng".into(), n, 11),
+                Token::INTEGER("12345".to_string(), n, 20),
+                Token::RPAR(n, 26),
+                Token::COMMENT("; comment".into(), n, 28)
+            ]);
+        }
+    }
+
+    #[test]
+    fn checkpoint_restore() {
+        let mut lexer = IOLexer::new(FakeFile::with_data("(define x\n  42 )".to_string()));
+        lexer.next().ok().unwrap();
+        let state  = lexer.checkpoint();
+        let first  = lexer.next().ok().unwrap();
+        let second = lexer.next().ok().unwrap();
+        assert_eq!(first, Token::IDENT("define".into(), 1, 2));
+        assert_eq!(second, Token::IDENT("x".into(), 1, 9));
+
+        assert_eq!(lexer.restore(state), Ok(()));
+        assert_eq!(lexer.next().ok().unwrap(), first);
+        assert_eq!(lexer.next().ok().unwrap(), second);
+        assert_eq!(lexer.next().ok().unwrap(), Token::INTEGER("42".to_string(), 2, 3));
+    }
+
+    #[test]
+    fn checkpoint_restore_across_refill() {
+        let data      = format!("{}abc λ def", " ".repeat(BUFFER_SIZE - 6));
+        let mut lexer = IOLexer::new(FakeFile::with_data(data));
+        let first     = lexer.next().ok().unwrap();
+        let state     = lexer.checkpoint();
+        assert_eq!(first, Token::IDENT("abc".into(), 1, BUFFER_SIZE as u32 - 5));
+        assert_eq!(lexer.get(), Some(' '));
+        assert_eq!(lexer.get(), Some('λ'));
+        assert_eq!(lexer.next().ok().unwrap(), Token::IDENT("def".into(), 1, BUFFER_SIZE as u32 + 1));
+
+        assert_eq!(lexer.restore(state), Ok(()));
+        assert_eq!(lexer.get(), Some(' '));
+        assert_eq!(lexer.get(), Some('λ'));
+        assert_eq!(lexer.next().ok().unwrap(), Token::IDENT("def".into(), 1, BUFFER_SIZE as u32 + 1));
+    }
+
+    #[test]
+    fn restore_out_of_window() {
+        let data      = format!("a{}", " ".repeat(4 * BUFFER_SIZE));
+        let mut lexer = IOLexer::new(FakeFile::with_data(data));
+        let state     = lexer.checkpoint();
+        while lexer.get().is_some() {}
+        assert_eq!(lexer.restore(state), Err(LexError::REWIND(1, 1)));
+        assert_eq!(lexer.get(), None);
+        assert_eq!(lexer.next(), Err(LexError::END(1, 4 * BUFFER_SIZE as u32 + 2)));
+    }
 }
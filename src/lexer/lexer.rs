@@ -1,35 +1,113 @@
-#[derive(PartialEq, Debug)]
-pub enum Token {
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+/* the text of comments, strings and identifiers is borrowed from the input when the lexer reads
+   from a &str and the token's text appears there as-is, and owned otherwise */
+#[derive(PartialEq, Debug, Clone)]
+pub enum Token<'a> {
     LPAR(u32, u32),
     RPAR(u32, u32),
-    COMMENT(String, u32, u32),
-    STRING(String, u32, u32),
+    COMMENT(Cow<'a, str>, u32, u32),
+    STRING(Cow<'a, str>, u32, u32),
     INTEGER(String, u32, u32),
     FLOAT(String, u32, u32),
-    IDENT(String, u32, u32)
+    RATIONAL(String, u32, u32),
+    IDENT(Cow<'a, str>, u32, u32),
+    BOOLEAN(bool, u32, u32),
+    CHAR(char, u32, u32),
+    QUOTE(u32, u32),
+    QUASIQUOTE(u32, u32),
+    UNQUOTE(u32, u32),
+    SPLICE(u32, u32),
+    VECTOR(u32, u32),
+    DOT(u32, u32)
 }
 
-impl Token {
-    fn number(string: String, is_float: bool, line: u32, chr: u32) -> Token {
+impl<'a> Token<'a> {
+    fn number(string: String, is_float: bool, line: u32, chr: u32) -> Token<'a> {
         if is_float {
             Token::FLOAT(string, line, chr)
         } else {
             Token::INTEGER(string, line, chr)
         }
     }
+
+    /* a copy that no longer borrows from the input */
+    pub fn into_owned(self) -> Token<'static> {
+        match self {
+            Token::LPAR(line, chr)          => Token::LPAR(line, chr),
+            Token::RPAR(line, chr)          => Token::RPAR(line, chr),
+            Token::COMMENT(s, line, chr)    => Token::COMMENT(Cow::Owned(s.into_owned()), line, chr),
+            Token::STRING(s, line, chr)     => Token::STRING(Cow::Owned(s.into_owned()), line, chr),
+            Token::INTEGER(s, line, chr)    => Token::INTEGER(s, line, chr),
+            Token::FLOAT(s, line, chr)      => Token::FLOAT(s, line, chr),
+            Token::RATIONAL(s, line, chr)   => Token::RATIONAL(s, line, chr),
+            Token::IDENT(s, line, chr)      => Token::IDENT(Cow::Owned(s.into_owned()), line, chr),
+            Token::BOOLEAN(b, line, chr)    => Token::BOOLEAN(b, line, chr),
+            Token::CHAR(c, line, chr)       => Token::CHAR(c, line, chr),
+            Token::QUOTE(line, chr)         => Token::QUOTE(line, chr),
+            Token::QUASIQUOTE(line, chr)    => Token::QUASIQUOTE(line, chr),
+            Token::UNQUOTE(line, chr)       => Token::UNQUOTE(line, chr),
+            Token::SPLICE(line, chr)        => Token::SPLICE(line, chr),
+            Token::VECTOR(line, chr)        => Token::VECTOR(line, chr),
+            Token::DOT(line, chr)           => Token::DOT(line, chr)
+        }
+    }
+
+    pub fn position(&self) -> (u32, u32) {
+        match *self {
+            Token::LPAR(line, chr)          |
+            Token::RPAR(line, chr)          |
+            Token::QUOTE(line, chr)         |
+            Token::QUASIQUOTE(line, chr)    |
+            Token::UNQUOTE(line, chr)       |
+            Token::SPLICE(line, chr)        |
+            Token::VECTOR(line, chr)        |
+            Token::DOT(line, chr)           |
+            Token::COMMENT(_, line, chr)    |
+            Token::STRING(_, line, chr)     |
+            Token::INTEGER(_, line, chr)    |
+            Token::FLOAT(_, line, chr)      |
+            Token::RATIONAL(_, line, chr)   |
+            Token::IDENT(_, line, chr)      |
+            Token::BOOLEAN(_, line, chr)    |
+            Token::CHAR(_, line, chr)       => (line, chr)
+        }
+    }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum LexError {
     INVALID(char, u32, u32),
     UNTERMINATED(String, u32, u32),
     IDENT(String, u32, u32),
     INTEGER(String, u32, u32),
     FLOAT(String, u32, u32),
+    RATIONAL(String, u32, u32),
+    COMMENT(u32, u32),
+    CHAR(String, u32, u32),
+    /* a checkpoint at the line and column that the lexer can no longer go back to */
+    REWIND(u32, u32),
     END(u32, u32)
 }
 
 impl LexError {
+    pub fn position(&self) -> (u32, u32) {
+        match *self {
+            LexError::INVALID(_, line, chr)      |
+            LexError::UNTERMINATED(_, line, chr) |
+            LexError::IDENT(_, line, chr)        |
+            LexError::INTEGER(_, line, chr)      |
+            LexError::FLOAT(_, line, chr)        |
+            LexError::RATIONAL(_, line, chr)     |
+            LexError::CHAR(_, line, chr)         |
+            LexError::COMMENT(line, chr)         |
+            LexError::REWIND(line, chr)          |
+            LexError::END(line, chr)             => (line, chr)
+        }
+    }
+
     fn number(string: String, is_float: bool, line: u32, chr: u32) -> LexError {
         if is_float {
             LexError::FLOAT(string, line, chr)
@@ -39,15 +117,125 @@ impl LexError {
     }
 }
 
-pub trait Lexer {
+impl<'a> fmt::Display for Token<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Token::LPAR(..)                => write!(f, "("),
+            Token::RPAR(..)                => write!(f, ")"),
+            Token::QUOTE(..)               => write!(f, "'"),
+            Token::QUASIQUOTE(..)          => write!(f, "`"),
+            Token::UNQUOTE(..)             => write!(f, ","),
+            Token::SPLICE(..)              => write!(f, ",@"),
+            Token::VECTOR(..)              => write!(f, "#("),
+            Token::DOT(..)                 => write!(f, "."),
+            Token::COMMENT(ref s, _, _)    => write!(f, "{}", s),
+            Token::STRING(ref s, _, _)     => {
+                write!(f, "\"")?;
+                for c in s.chars() {
+                    match c {
+                        '"' | '\\' | '\n' => write!(f, "\\{}", c)?,
+                        _                 => write!(f, "{}", c)?
+                    }
+                }
+                write!(f, "\"")
+            },
+            Token::INTEGER(ref s, _, _)    |
+            Token::FLOAT(ref s, _, _)      |
+            Token::RATIONAL(ref s, _, _)   => write!(f, "{}", s),
+            Token::IDENT(ref s, _, _)      => write!(f, "{}", s),
+            Token::BOOLEAN(b, _, _)        => write!(f, "{}", if b { "#t" } else { "#f" }),
+            Token::CHAR(c, _, _)           => write_char(f, c)
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LexError::INVALID(c, line, chr)          => write!(f, "invalid character '{}' at line {}, column {}", c, line, chr),
+            LexError::UNTERMINATED(_, line, chr)     => write!(f, "unterminated string at line {}, column {}", line, chr),
+            LexError::IDENT(ref s, line, chr)        => write!(f, "invalid identifier \"{}\" at line {}, column {}", s, line, chr),
+            LexError::INTEGER(ref s, line, chr)      => write!(f, "invalid integer \"{}\" at line {}, column {}", s, line, chr),
+            LexError::FLOAT(ref s, line, chr)        => write!(f, "invalid float \"{}\" at line {}, column {}", s, line, chr),
+            LexError::RATIONAL(ref s, line, chr)     => write!(f, "invalid rational \"{}\" at line {}, column {}", s, line, chr),
+            LexError::COMMENT(line, chr)             => write!(f, "unterminated block comment at line {}, column {}", line, chr),
+            LexError::CHAR(ref s, line, chr)         => write!(f, "invalid character \"{}\" at line {}, column {}", s, line, chr),
+            LexError::REWIND(line, chr)              => write!(f, "cannot rewind to line {}, column {}, the input there is gone", line, chr),
+            LexError::END(line, chr)                 => write!(f, "unexpected end of input at line {}, column {}", line, chr)
+        }
+    }
+}
+
+impl Error for LexError {}
+
+/* the characters written `#\name` rather than as themselves */
+pub const CHAR_NAMES: [(&str, char); 10] = [
+    ("space", ' '),
+    ("newline", '\n'),
+    ("tab", '\t'),
+    ("return", '\r'),
+    ("null", '\0'),
+    ("alarm", '\u{7}'),
+    ("backspace", '\u{8}'),
+    ("escape", '\u{1b}'),
+    ("delete", '\u{7f}'),
+    ("nul", '\0')
+];
+
+/* `c` as a character literal that reads back as `c` */
+pub fn write_char(f: &mut fmt::Formatter, c: char) -> fmt::Result {
+    match CHAR_NAMES.iter().find(|&&(_, named)| named == c) {
+        Some(&(name, _))       => write!(f, "#\\{}", name),
+        None if c.is_control() => write!(f, "#\\x{:x}", c as u32),
+        None                   => write!(f, "#\\{}", c)
+    }
+}
+
+/* a saved lexer position: byte offset into the input plus the line/chr counters */
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct LexerState {
+    pub offset: usize,
+    pub line:   u32,
+    pub chr:    u32
+}
+
+pub trait Lexer<'a> {
     fn get(&mut self) -> Option<char>;
     fn peek(&self) -> Option<char>;
-    fn set_line(&mut self, line: u32) -> ();
-    fn set_chr(&mut self, chr: u32) -> ();
+    fn set_line(&mut self, line: u32);
+    fn set_chr(&mut self, chr: u32);
     fn line(&self) -> u32;
     fn chr(&self) -> u32;
+    fn checkpoint(&self) -> LexerState;
+    fn restore(&mut self, state: LexerState) -> Result<(), LexError>;
+    fn skip_comments(&self) -> bool;
+    fn set_skip_comments(&mut self, skip: bool);
+    fn fold_case(&self) -> bool;
+    fn set_fold_case(&mut self, fold: bool);
+
+    /* the input between two byte offsets, for lexers that can lend it out for as long as 'a */
+    fn slice(&self, _start: usize, _end: usize) -> Option<&'a str> {
+        None
+    }
+
+    fn offset(&self) -> usize {
+        self.checkpoint().offset
+    }
+
+    /* lexers that lend out their input don't need to build up the text of a token as they go */
+    fn borrows(&self) -> bool {
+        self.slice(0, 0).is_some()
+    }
 
-    fn count(&mut self, c: char) -> () {
+    /* the text read since `start`, or `owned` when it can't be borrowed */
+    fn text(&self, start: usize, owned: String) -> Cow<'a, str> {
+        match self.slice(start, self.offset()) {
+            Some(s) => Cow::Borrowed(s),
+            None    => Cow::Owned(owned)
+        }
+    }
+
+    fn count(&mut self, c: char) {
         let line = self.line();
         let chr  = self.chr();
 
@@ -62,30 +250,45 @@ pub trait Lexer {
         }
     }
 
-    fn next(&mut self) -> Result<Token, LexError> {
-        self.consume_whitespace();
-        match self.peek() {
-            None    => Err(LexError::END(self.line(), self.chr())),
-            Some(_) => self.read_token()
+    /* with skip_comments set, comments of every kind are read and thrown away here */
+    fn next(&mut self) -> Result<Token<'a>, LexError> {
+        loop {
+            self.consume_whitespace();
+            match self.read_token()? {
+                Token::COMMENT(..) if self.skip_comments() => (),
+                token                                      => return Ok(token)
+            }
         }
     }
 
-    fn read_token(&mut self) -> Result<Token, LexError> {
+    fn read_token(&mut self) -> Result<Token<'a>, LexError> {
         match self.peek() {
             Some(c) => match c {
-                '('                     => self.lpar(),
-                ')'                     => self.rpar(),
-                ';'                     => self.comment(),
-                '"'                     => self.string(),
-                '0' ... '9' | '-' | '.' => self.number(),
-                'A' ... 'z'             => self.ident(),
-                _                       => Err(LexError::INVALID(c, self.line(), self.chr()))
+                '('                           => self.lpar(),
+                ')'                           => self.rpar(),
+                '\''                          => self.quote(),
+                '`'                           => self.quasiquote(),
+                ','                           => self.unquote(),
+                ';'                           => self.comment(),
+                '"'                           => self.string(),
+                '#'                           => self.hash(),
+                '0' ..= '9' | '-' | '+' | '.' => self.number(),
+                _ if is_ident_start(c)        => self.ident(),
+                _                             => self.invalid()
             },
             None => Err(LexError::END(self.line(), self.chr()))
         }
     }
 
-    fn consume_whitespace(&mut self) -> () {
+    /* the offending character is consumed so lexing can carry on after the error */
+    fn invalid(&mut self) -> Result<Token<'a>, LexError> {
+        let line = self.line();
+        let chr  = self.chr();
+        let c    = self.get().unwrap();
+        Err(LexError::INVALID(c, line, chr))
+    }
+
+    fn consume_whitespace(&mut self) {
         while let Some(c) = self.peek() {
             if !c.is_whitespace() {
                 break;
@@ -95,36 +298,205 @@ pub trait Lexer {
         }
     }
 
-    fn lpar(&mut self) -> Result<Token, LexError> {
+    fn lpar(&mut self) -> Result<Token<'a>, LexError> {
         let line = self.line();
         let chr  = self.chr();
         self.get();
         Ok(Token::LPAR(line, chr))
     }
 
-    fn rpar(&mut self) -> Result<Token, LexError> {
+    fn rpar(&mut self) -> Result<Token<'a>, LexError> {
         let line = self.line();
         let chr  = self.chr();
         self.get();
         Ok(Token::RPAR(line, chr))
     }
 
+    fn quote(&mut self) -> Result<Token<'a>, LexError> {
+        let line = self.line();
+        let chr  = self.chr();
+        self.get();
+        Ok(Token::QUOTE(line, chr))
+    }
+
+    fn quasiquote(&mut self) -> Result<Token<'a>, LexError> {
+        let line = self.line();
+        let chr  = self.chr();
+        self.get();
+        Ok(Token::QUASIQUOTE(line, chr))
+    }
+
+    /* `,` or `,@` */
+    fn unquote(&mut self) -> Result<Token<'a>, LexError> {
+        let line = self.line();
+        let chr  = self.chr();
+        self.get();
+        if self.peek() == Some('@') {
+            self.get();
+            return Ok(Token::SPLICE(line, chr))
+        }
+        Ok(Token::UNQUOTE(line, chr))
+    }
+
     // consume until end of line
-    fn comment(&mut self) -> Result<Token, LexError> {
-        let line        = self.line();
-        let chr         = self.chr();
-        let mut comment = String::new();
+    fn comment(&mut self) -> Result<Token<'a>, LexError> {
+        let line  = self.line();
+        let chr   = self.chr();
+        let start = self.offset();
+        self.line_comment(start, String::new(), line, chr)
+    }
+
+    /* `comment` is what's been read since `start`. the newline is left for consume_whitespace,
+       like any other whitespace after a token */
+    fn line_comment(&mut self, start: usize, mut comment: String, line: u32, chr: u32) -> Result<Token<'a>, LexError> {
+        let borrows = self.borrows();
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                break
+            }
+            self.get();
+            if !borrows {
+                comment.push(c);
+            }
+        }
+        let text = match self.text(start, comment) {
+            Cow::Borrowed(s) => Cow::Borrowed(s.trim()),
+            Cow::Owned(s)    => Cow::Owned(s.trim().to_string())
+        };
+        Ok(Token::COMMENT(text, line, chr))
+    }
+
+    /* `#|` block comments, which nest, run to the matching `|#` */
+    fn block_comment(&mut self, start: usize, line: u32, chr: u32) -> Result<Token<'a>, LexError> {
+        let borrows     = self.borrows();
+        let mut comment = String::from("#");
+        let mut depth   = 0;
+        let mut prev    = Some('#');
         while let Some(c) = self.get() {
-            if c != '\n' {
+            if !borrows {
                 comment.push(c);
-            } else {
-                break;
+            }
+            match (prev, c) {
+                (Some('#'), '|') => depth += 1,
+                (Some('|'), '#') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(Token::COMMENT(self.text(start, comment), line, chr))
+                    }
+                },
+                _ => {
+                    prev = Some(c);
+                    continue
+                }
+            }
+            /* a character can't both close one delimiter and open the next */
+            prev = None;
+        }
+        Err(LexError::COMMENT(line, chr))
+    }
+
+    /* `#;` comments out the datum that follows it, which is read as tokens and dropped */
+    fn datum_comment(&mut self, line: u32, chr: u32) -> Result<Token<'a>, LexError> {
+        self.get();
+        let mut depth = 0;
+        loop {
+            self.consume_whitespace();
+            match self.read_token()? {
+                Token::LPAR(..)                  |
+                Token::VECTOR(..)                => depth += 1,
+                Token::RPAR(..) if depth > 0     => depth -= 1,
+                Token::RPAR(line, chr)           => return Err(LexError::INVALID(')', line, chr)),
+                Token::QUOTE(..)                 |
+                Token::QUASIQUOTE(..)            |
+                Token::UNQUOTE(..)               |
+                Token::SPLICE(..)                |
+                Token::COMMENT(..)               => continue,
+                _                                => ()
+            }
+            if depth == 0 {
+                return Ok(Token::COMMENT(Cow::Borrowed("#;"), line, chr))
             }
         }
-        Ok(Token::COMMENT(comment.trim().to_string(), line, chr))
     }
 
-    fn string(&mut self) -> Result<Token, LexError> {
+    /* `#\a`, `#\space` or `#\x41`. the first character is taken even if it's a delimiter, as in `#\(` */
+    fn char_literal(&mut self, line: u32, chr: u32) -> Result<Token<'a>, LexError> {
+        self.get();
+        let mut name = match self.get() {
+            Some(c) => c.to_string(),
+            None    => return Err(LexError::CHAR("#\\".to_string(), line, chr))
+        };
+        while let Some(c) = self.peek() {
+            if is_delimiter(c) {
+                break
+            }
+            self.get();
+            name.push(c);
+        }
+
+        let mut chars = name.chars();
+        let first     = chars.next().unwrap();
+        if chars.next().is_none() {
+            return Ok(Token::CHAR(first, line, chr))
+        }
+        if let Some(&(_, c)) = CHAR_NAMES.iter().find(|&&(known, _)| known == name) {
+            return Ok(Token::CHAR(c, line, chr))
+        }
+        match name.strip_prefix('x').and_then(|hex| u32::from_str_radix(hex, 16).ok()).and_then(char::from_u32) {
+            Some(c) => Ok(Token::CHAR(c, line, chr)),
+            None    => Err(LexError::CHAR(format!("#\\{}", name), line, chr))
+        }
+    }
+
+    /* `#` prefixed syntax: the booleans, characters, `#(` opening a vector, block comments and datum comments */
+    fn hash(&mut self) -> Result<Token<'a>, LexError> {
+        let line     = self.line();
+        let chr      = self.chr();
+        let start    = self.offset();
+        let mut name = String::new();
+        self.get();
+
+        match self.peek() {
+            Some('\\') => return self.char_literal(line, chr),
+            Some('(')  => {
+                self.get();
+                return Ok(Token::VECTOR(line, chr))
+            },
+            Some('|')  => return self.block_comment(start, line, chr),
+            Some(';')  => return self.datum_comment(line, chr),
+            _          => ()
+        }
+
+        while let Some(c) = self.peek() {
+            if is_delimiter(c) {
+                break
+            }
+            self.get();
+            name.push(c);
+        }
+
+        match name.as_str() {
+            "t" | "true"                                        => Ok(Token::BOOLEAN(true, line, chr)),
+            "f" | "false"                                       => Ok(Token::BOOLEAN(false, line, chr)),
+            _ if self.directive(&name)                          => Ok(Token::COMMENT(self.text(start, format!("#{}", name)), line, chr)),
+            /* any other `#!` is an interpreter line, only allowed at the very start of a script */
+            _ if name.starts_with('!') && line == 1 && chr == 1 => self.line_comment(start, format!("#{}", name), line, chr),
+            _                                                   => Err(LexError::INVALID('#', line, chr))
+        }
+    }
+
+    /* `#!fold-case` and `#!no-fold-case` switch case folding for the rest of the input, returns
+       whether `name` was one of them */
+    fn directive(&mut self, name: &str) -> bool {
+        match name {
+            "!fold-case"    => self.set_fold_case(true),
+            "!no-fold-case" => self.set_fold_case(false),
+            _               => return false
+        }
+        true
+    }
+
+    fn string(&mut self) -> Result<Token<'a>, LexError> {
         let mut string = String::new();
         let start_line = self.line();
         let start_chr  = self.chr();
@@ -132,72 +504,141 @@ pub trait Lexer {
         /* consume first quotation mark */
         self.get();
 
+        /* while the string has no escapes its text is the input as-is, which a borrowing lexer
+           hands out rather than copying into `string` */
+        let start     = self.offset();
+        let mut owned = !self.borrows();
+        let mut end   = start;
         while let Some(c) = self.get() {
             match c {
                 /* if we get a '\', the next character, unconditionally take the next character */
-                '\\' => match self.get() {
-                    Some(next) => string.push(next),
-                    None       => break
+                '\\' => {
+                    if !owned {
+                        string = self.slice(start, end).unwrap().to_string();
+                        owned  = true;
+                    }
+                    match self.get() {
+                        Some(next) => string.push(next),
+                        None       => break
+                    }
                 },
                 '\n' => break,
-                '\"' => return Ok(Token::STRING(string, start_line, start_chr)),
-                _    => string.push(c)
+                '\"' => {
+                    let text = if owned { Cow::Owned(string) } else { Cow::Borrowed(self.slice(start, end).unwrap()) };
+                    return Ok(Token::STRING(text, start_line, start_chr))
+                },
+                _    => if owned { string.push(c) }
             };
+            end = self.offset();
+        }
+        if !owned {
+            string = self.slice(start, end).unwrap().to_string();
         }
         Err(LexError::UNTERMINATED(string, start_line, start_chr))
     }
 
-    fn number(&mut self) -> Result<Token, LexError> {
-        let mut number = String::new();
-        let start_line = self.line();
-        let start_chr  = self.chr();
-        let mut float  = false;
+    fn number(&mut self) -> Result<Token<'a>, LexError> {
+        let mut number   = String::new();
+        let start_line   = self.line();
+        let start_chr    = self.chr();
+        let start        = self.offset();
+        let mut float    = false;
+        let mut rational = false;
 
-        if let Some('-') = self.peek() {
-            number.push('-');
+        /* a sign not followed by a digit or '.' starts an identifier, e.g. `+` or `-x` */
+        if let Some(sign @ '-') | Some(sign @ '+') = self.peek() {
+            number.push(sign);
             self.get();
+            match self.peek() {
+                Some('0' ..= '9') | Some('.') => (),
+                _                             => return self.read_ident(start, number, start_line, start_chr)
+            }
         }
 
-        while let Some(c) = self.get() {
+        while let Some(c) = self.peek() {
+            if is_delimiter(c) {
+                break
+            }
+            self.get();
             number.push(c);
             match c {
-                '0' ... '9' => (),
+                '0' ..= '9' => (),
+                '.' if rational => return Err(LexError::RATIONAL(number, start_line, start_chr)),
+                /* `...`, the ellipsis used by syntax-rules, is an identifier */
+                '.' if number == ".." => return self.read_ident(start, number, start_line, start_chr),
                 '.' => if float {
                     return Err(LexError::FLOAT(number, start_line, start_chr))
                 } else {
                     float = true
                 },
-                _ => if c.is_whitespace() {
-                    break
-                } else {
-                    return Err(LexError::number(number, float, start_line, start_chr))
-                }
+                /* an exact fraction `n/m` */
+                '/' if !float && !rational && number.bytes().any(|b| b.is_ascii_digit()) => rational = true,
+                _ if rational => return Err(LexError::RATIONAL(number, start_line, start_chr)),
+                _ => return Err(LexError::number(number, float, start_line, start_chr))
+            }
+        }
+
+        /* a lone '.' separates the tail of a dotted list */
+        if number == "." {
+            return Ok(Token::DOT(start_line, start_chr))
+        }
+
+        if rational {
+            return if number.ends_with('/') {
+                Err(LexError::RATIONAL(number, start_line, start_chr))
+            } else {
+                Ok(Token::RATIONAL(number, start_line, start_chr))
             }
         }
 
-        Ok(Token::number(number.trim().to_string(), float, start_line, start_chr))
+        Ok(Token::number(number, float, start_line, start_chr))
     }
 
-    fn ident(&mut self) -> Result<Token, LexError> {
-        let invalid    = vec!['[', ']', '{', '}', '(', ')', '|', '\\', '/', '\'', '\"', '#', ','];
+    fn ident(&mut self) -> Result<Token<'a>, LexError> {
         let start_line = self.line();
         let start_chr  = self.chr();
-        let mut ident  = String::new();
+        let start      = self.offset();
+        let ident      = self.get().into_iter().collect();
+        self.read_ident(start, ident, start_line, start_chr)
+    }
 
-        while let Some(c) = self.get() {
-            if invalid.contains(&c) {
-                return Err(LexError::IDENT(ident, start_line, start_chr))
-            } else if c.is_whitespace() {
+    /* `ident` is what's been read since `start` */
+    fn read_ident(&mut self, start: usize, mut ident: String, start_line: u32, start_chr: u32) -> Result<Token<'a>, LexError> {
+        let invalid = ['[', ']', '{', '}', '|', '\\', '\'', '#', ','];
+        let borrows = self.borrows();
+
+        while let Some(c) = self.peek() {
+            if is_delimiter(c) {
                 break
-            } else {
+            }
+            if invalid.contains(&c) {
+                let text = self.text(start, ident).into_owned();
+                self.get();
+                return Err(LexError::IDENT(text, start_line, start_chr))
+            }
+            self.get();
+            if !borrows {
                 ident.push(c)
             }
         }
 
-        Ok(Token::IDENT(ident, start_line, start_chr))
+        let text = self.text(start, ident);
+        if self.fold_case() && text.chars().any(|c| !c.to_lowercase().eq(Some(c))) {
+            return Ok(Token::IDENT(Cow::Owned(text.to_lowercase()), start_line, start_chr))
+        }
+        Ok(Token::IDENT(text, start_line, start_chr))
     }
 }
 
+/* characters that end an identifier or number without being part of it */
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || c == '(' || c == ')' || c == '"' || c == ';'
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || "!$%&*/:<=>?^_~".contains(c)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,21 +662,204 @@ mod tests {
     fn read_string() {
         let mut lexer = StringLexer::new("\"\\\"Hello\\\", world!\\\n\"".to_string());
         let token = lexer.next().ok().unwrap();
-        assert_eq!(token, Token::STRING("\"Hello\", world!\n".to_string(), 1, 1));
+        assert_eq!(token, Token::STRING("\"Hello\", world!\n".into(), 1, 1));
     }
 
     #[test]
     fn read_comment() {
         let mut lexer = StringLexer::new("; this is some code that does some stuff".to_string());
         let token = lexer.next().ok().unwrap();
-        assert_eq!(token, Token::COMMENT("; this is some code that does some stuff".to_string(), 1, 1));
+        assert_eq!(token, Token::COMMENT("; this is some code that does some stuff".into(), 1, 1));
+    }
+
+    #[test]
+    fn read_after_invalid() {
+        let mut lexer = StringLexer::new("[1".to_string());
+        assert_eq!(lexer.next(), Err(LexError::INVALID('[', 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("1".to_string(), 1, 2)));
+    }
+
+    #[test]
+    fn read_char() {
+        let mut lexer = StringLexer::new("#\\a #\\A #\\( #\\space #\\x41 #\\λ #\\ (".to_string());
+        assert_eq!(lexer.next(), Ok(Token::CHAR('a', 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::CHAR('A', 1, 5)));
+        assert_eq!(lexer.next(), Ok(Token::CHAR('(', 1, 9)));
+        assert_eq!(lexer.next(), Ok(Token::CHAR(' ', 1, 13)));
+        assert_eq!(lexer.next(), Ok(Token::CHAR('A', 1, 21)));
+        assert_eq!(lexer.next(), Ok(Token::CHAR('λ', 1, 27)));
+        assert_eq!(lexer.next(), Ok(Token::CHAR(' ', 1, 31)));
+        assert_eq!(lexer.next(), Ok(Token::LPAR(1, 34)));
+    }
+
+    #[test]
+    fn error_char() {
+        assert_eq!(StringLexer::new("#\\bogus".to_string()).next(), Err(LexError::CHAR("#\\bogus".to_string(), 1, 1)));
+        assert_eq!(StringLexer::new("#\\xd800".to_string()).next(), Err(LexError::CHAR("#\\xd800".to_string(), 1, 1)));
+        assert_eq!(StringLexer::new("#\\".to_string()).next(), Err(LexError::CHAR("#\\".to_string(), 1, 1)));
+    }
+
+    #[test]
+    fn display_char() {
+        for &(c, written) in &[('a', "#\\a"), (' ', "#\\space"), ('\n', "#\\newline"), ('\u{1}', "#\\x1"), ('(', "#\\(")] {
+            assert_eq!(Token::CHAR(c, 1, 1).to_string(), written);
+        }
+    }
+
+    #[test]
+    fn read_vector() {
+        let mut lexer = StringLexer::new("#(1 #t)".to_string());
+        assert_eq!(lexer.next(), Ok(Token::VECTOR(1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("1".to_string(), 1, 3)));
+        assert_eq!(lexer.next(), Ok(Token::BOOLEAN(true, 1, 5)));
+        assert_eq!(lexer.next(), Ok(Token::RPAR(1, 7)));
+    }
+
+    #[test]
+    fn read_block_comment() {
+        let mut lexer = StringLexer::new("#| outer #| inner |# |#1 #||#".to_string());
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#| outer #| inner |# |#".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("1".to_string(), 1, 24)));
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#||#".into(), 1, 26)));
+        assert_eq!(StringLexer::new("#| open #| |#".to_string()).next(), Err(LexError::COMMENT(1, 1)));
+    }
+
+    #[test]
+    fn read_datum_comment() {
+        let mut lexer = StringLexer::new("#;(a (b) 'c) 1 #; ' x 2 (#;y)".to_string());
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#;".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("1".to_string(), 1, 14)));
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#;".into(), 1, 16)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("2".to_string(), 1, 23)));
+        assert_eq!(lexer.next(), Ok(Token::LPAR(1, 25)));
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#;".into(), 1, 26)));
+        assert_eq!(lexer.next(), Ok(Token::RPAR(1, 29)));
+
+        let mut lexer = StringLexer::new("#;#(1 (2)) 3 #;'#(a) 4".to_string());
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#;".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("3".to_string(), 1, 12)));
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#;".into(), 1, 14)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("4".to_string(), 1, 22)));
+
+        let mut lexer = StringLexer::new("(#;)".to_string());
+        assert_eq!(lexer.next(), Ok(Token::LPAR(1, 1)));
+        assert_eq!(lexer.next(), Err(LexError::INVALID(')', 1, 4)));
+    }
+
+    #[test]
+    fn read_after_comment() {
+        let mut lexer = StringLexer::new(";c\n42".to_string());
+        assert_eq!(lexer.next(), Ok(Token::COMMENT(";c".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("42".to_string(), 2, 1)));
+
+        let mut lexer = StringLexer::new(";c\r\n\n  42 ;d".to_string());
+        lexer.set_skip_comments(true);
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("42".to_string(), 3, 3)));
+        assert_eq!(lexer.next(), Err(LexError::END(3, 8)));
+    }
+
+    #[test]
+    fn read_comment_at_end() {
+        let mut lexer = StringLexer::new("1 ;c".to_string());
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("1".to_string(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::COMMENT(";c".into(), 1, 3)));
+        assert_eq!(lexer.next(), Err(LexError::END(1, 5)));
+    }
+
+    #[test]
+    fn read_shebang() {
+        let mut lexer = StringLexer::new("#!/usr/bin/env scheme\n(display 1)".to_string());
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#!/usr/bin/env scheme".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::LPAR(2, 1)));
+        assert_eq!(lexer.next(), Ok(Token::IDENT("display".into(), 2, 2)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("1".to_string(), 2, 10)));
+        assert_eq!(lexer.next(), Ok(Token::RPAR(2, 11)));
+        assert_eq!(lexer.next(), Err(LexError::END(2, 12)));
+    }
+
+    #[test]
+    fn read_directive_at_start() {
+        let mut lexer = StringLexer::new("#!fold-case (display 'FOO)\nBAR".to_string());
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#!fold-case".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::LPAR(1, 13)));
+        assert_eq!(lexer.next(), Ok(Token::IDENT("display".into(), 1, 14)));
+        assert_eq!(lexer.next(), Ok(Token::QUOTE(1, 22)));
+        assert_eq!(lexer.next(), Ok(Token::IDENT("foo".into(), 1, 23)));
+        assert_eq!(lexer.next(), Ok(Token::RPAR(1, 26)));
+        assert_eq!(lexer.next(), Ok(Token::IDENT("bar".into(), 2, 1)));
+
+        let mut lexer = StringLexer::new("#!/bin/scheme (x)\n1".to_string());
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#!/bin/scheme (x)".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("1".to_string(), 2, 1)));
+    }
+
+    #[test]
+    fn read_shebang_after_start() {
+        let mut lexer = StringLexer::new("1 #!/bin/scheme".to_string());
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("1".to_string(), 1, 1)));
+        assert_eq!(lexer.next(), Err(LexError::INVALID('#', 1, 3)));
+        assert_eq!(StringLexer::new(" #!x".to_string()).next(), Err(LexError::INVALID('#', 1, 2)));
+    }
+
+    #[test]
+    fn fold_case() {
+        let mut lexer = StringLexer::new("CAR car Foo \"Str\" 1E".to_string());
+        lexer.set_fold_case(true);
+        assert_eq!(lexer.next(), Ok(Token::IDENT("car".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::IDENT("car".into(), 1, 5)));
+        assert_eq!(lexer.next(), Ok(Token::IDENT("foo".into(), 1, 9)));
+        assert_eq!(lexer.next(), Ok(Token::STRING("Str".into(), 1, 13)));
+        assert_eq!(lexer.next(), Err(LexError::INTEGER("1E".to_string(), 1, 19)));
+
+        let mut lexer = StringLexer::new("CAR car".to_string());
+        assert_eq!(lexer.next(), Ok(Token::IDENT("CAR".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::IDENT("car".into(), 1, 5)));
+    }
+
+    #[test]
+    fn fold_case_directives() {
+        let mut lexer = StringLexer::new("A #!fold-case B #!no-fold-case C".to_string());
+        assert_eq!(lexer.next(), Ok(Token::IDENT("A".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#!fold-case".into(), 1, 3)));
+        assert_eq!(lexer.next(), Ok(Token::IDENT("b".into(), 1, 15)));
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("#!no-fold-case".into(), 1, 17)));
+        assert_eq!(lexer.next(), Ok(Token::IDENT("C".into(), 1, 32)));
+
+        let mut lexer = StringLexer::new("#!fold-case\nX".to_string());
+        lexer.set_skip_comments(true);
+        assert_eq!(lexer.next(), Ok(Token::IDENT("x".into(), 2, 1)));
+    }
+
+    #[test]
+    fn skip_comments() {
+        let source    = "; a\n(1) ; b";
+        let mut lexer = StringLexer::new(source.to_string());
+        lexer.set_skip_comments(true);
+        assert_eq!(lexer.next(), Ok(Token::LPAR(2, 1)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("1".to_string(), 2, 2)));
+        assert_eq!(lexer.next(), Ok(Token::RPAR(2, 3)));
+        assert_eq!(lexer.next(), Err(LexError::END(2, 8)));
+
+        let mut lexer = StringLexer::new(source.to_string());
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("; a".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::LPAR(2, 1)));
+        assert_eq!(lexer.next(), Ok(Token::INTEGER("1".to_string(), 2, 2)));
+        assert_eq!(lexer.next(), Ok(Token::RPAR(2, 3)));
+        assert_eq!(lexer.next(), Ok(Token::COMMENT("; b".into(), 2, 5)));
+    }
+
+    #[test]
+    fn skip_block_and_datum_comments() {
+        let mut lexer = StringLexer::new("#| a |# #;(b c) d".to_string());
+        lexer.set_skip_comments(true);
+        assert_eq!(lexer.next(), Ok(Token::IDENT("d".into(), 1, 17)));
     }
 
     #[test]
     fn read_ident() {
         let mut lexer = StringLexer::new("an-!@$%^&*-+=~?.ident-can-have-all-these-chars".to_string());
         let token = lexer.next().ok().unwrap();
-        assert_eq!(token, Token::IDENT("an-!@$%^&*-+=~?.ident-can-have-all-these-chars".to_string(), 1, 1));
+        assert_eq!(token, Token::IDENT("an-!@$%^&*-+=~?.ident-can-have-all-these-chars".into(), 1, 1));
     }
 
     #[test]
@@ -259,6 +883,22 @@ mod tests {
         assert_eq!(token, LexError::INTEGER("12f".to_string(), 1, 1));
     }
 
+    #[test]
+    fn read_rational() {
+        let mut lexer = StringLexer::new("3/4 -10/20)".to_string());
+        assert_eq!(lexer.next().ok().unwrap(), Token::RATIONAL("3/4".to_string(), 1, 1));
+        assert_eq!(lexer.next().ok().unwrap(), Token::RATIONAL("-10/20".to_string(), 1, 5));
+        assert_eq!(lexer.next().ok().unwrap(), Token::RPAR(1, 11));
+    }
+
+    #[test]
+    fn read_invalid_rational() {
+        assert_eq!(StringLexer::new("1/".to_string()).next().err().unwrap(), LexError::RATIONAL("1/".to_string(), 1, 1));
+        assert_eq!(StringLexer::new("1/2/3".to_string()).next().err().unwrap(), LexError::RATIONAL("1/2/".to_string(), 1, 1));
+        assert_eq!(StringLexer::new("1/2.5".to_string()).next().err().unwrap(), LexError::RATIONAL("1/2.".to_string(), 1, 1));
+        assert_eq!(StringLexer::new("1.5/2".to_string()).next().err().unwrap(), LexError::FLOAT("1.5/".to_string(), 1, 1));
+    }
+
     #[test]
     fn read_float_dot() {
         let mut lexer = StringLexer::new("12345.".to_string());
@@ -333,21 +973,21 @@ mod tests {
             ".to_string());
 
         let expected = vec![
-            Token::COMMENT("; hello, this is a comment".to_string(), 1, 1),
+            Token::COMMENT("; hello, this is a comment".into(), 1, 1),
             Token::LPAR(2, 1),
-            Token::STRING("this is a \"string\" with some escape chars".to_string(), 2, 2),
+            Token::STRING("this is a \"string\" with some escape chars".into(), 2, 2),
             Token::RPAR(2, 47),
             Token::LPAR(3, 1),
             Token::RPAR(3, 5),
-            Token::COMMENT("; this is a comment after something on a line".to_string(), 3, 7),
+            Token::COMMENT("; this is a comment after something on a line".into(), 3, 7),
             Token::LPAR(4, 1),
             Token::LPAR(4, 17),
-            Token::STRING("s p a c e".to_string(), 4, 19),
+            Token::STRING("s p a c e".into(), 4, 19),
             Token::RPAR(4, 31),
-            Token::COMMENT("; space".to_string(), 4, 33),
+            Token::COMMENT("; space".into(), 4, 33),
             Token::INTEGER("12345".to_string(), 5, 1),
-            Token::IDENT("is-a-number".to_string(), 5, 7),
-            Token::IDENT("so_is".to_string(), 5, 19),
+            Token::IDENT("is-a-number".into(), 5, 7),
+            Token::IDENT("so_is".into(), 5, 19),
             Token::FLOAT("-78.910".to_string(), 5, 25)
         ];
 
@@ -359,6 +999,155 @@ mod tests {
         assert_eq!(tokens, expected)
     }
 
+    #[test]
+    fn checkpoint_restore() {
+        let mut lexer = StringLexer::new("(define x\n  42 )".to_string());
+        lexer.next().ok().unwrap();
+        let state  = lexer.checkpoint();
+        let first  = lexer.next().ok().unwrap();
+        let second = lexer.next().ok().unwrap();
+        assert_eq!(first, Token::IDENT("define".into(), 1, 2));
+        assert_eq!(second, Token::IDENT("x".into(), 1, 9));
+
+        assert_eq!(lexer.restore(state), Ok(()));
+        assert_eq!(lexer.next().ok().unwrap(), first);
+        assert_eq!(lexer.next().ok().unwrap(), second);
+        assert_eq!(lexer.next().ok().unwrap(), Token::INTEGER("42".to_string(), 2, 3));
+    }
+
+    #[test]
+    fn display_token() {
+        assert_eq!(Token::LPAR(1, 1).to_string(), "(");
+        assert_eq!(Token::RPAR(1, 1).to_string(), ")");
+        assert_eq!(Token::STRING("hi".into(), 1, 1).to_string(), "\"hi\"");
+        assert_eq!(Token::STRING("a \"b\" \\".into(), 1, 1).to_string(), "\"a \\\"b\\\" \\\\\"");
+        assert_eq!(Token::IDENT("foo".into(), 1, 1).to_string(), "foo");
+        assert_eq!(Token::INTEGER("-12".to_string(), 1, 1).to_string(), "-12");
+        assert_eq!(Token::FLOAT("1.5".to_string(), 1, 1).to_string(), "1.5");
+        assert_eq!(Token::COMMENT("; note".into(), 1, 1).to_string(), "; note");
+        assert_eq!(Token::BOOLEAN(true, 1, 1).to_string(), "#t");
+        assert_eq!(Token::BOOLEAN(false, 1, 1).to_string(), "#f");
+    }
+
+    #[test]
+    fn display_string_rereads() {
+        let token     = Token::STRING("say \"hi\"\nback\\slash".into(), 1, 1);
+        let mut lexer = StringLexer::new(token.to_string());
+        assert_eq!(lexer.next().ok().unwrap(), token);
+    }
+
+    #[test]
+    fn display_error() {
+        assert_eq!(LexError::INVALID('#', 1, 6).to_string(), "invalid character '#' at line 1, column 6");
+        assert_eq!(LexError::UNTERMINATED("abc".to_string(), 3, 2).to_string(), "unterminated string at line 3, column 2");
+        assert_eq!(LexError::INTEGER("12f".to_string(), 2, 4).to_string(), "invalid integer \"12f\" at line 2, column 4");
+        assert_eq!(LexError::END(1, 2).to_string(), "unexpected end of input at line 1, column 2");
+    }
+
+    #[test]
+    fn read_booleans() {
+        let mut lexer = StringLexer::new("#t #f #true #false(#t)".to_string());
+        let expected  = vec![
+            Token::BOOLEAN(true, 1, 1),
+            Token::BOOLEAN(false, 1, 4),
+            Token::BOOLEAN(true, 1, 7),
+            Token::BOOLEAN(false, 1, 13),
+            Token::LPAR(1, 19),
+            Token::BOOLEAN(true, 1, 20),
+            Token::RPAR(1, 22)
+        ];
+
+        let mut tokens = vec![];
+        while let Ok(token) = lexer.next() {
+            tokens.push(token)
+        }
+
+        assert_eq!(tokens, expected)
+    }
+
+    #[test]
+    fn read_quasiquote() {
+        let mut lexer = StringLexer::new("`(a ,b ,@c)".to_string());
+        let expected  = vec![
+            Token::QUASIQUOTE(1, 1),
+            Token::LPAR(1, 2),
+            Token::IDENT("a".into(), 1, 3),
+            Token::UNQUOTE(1, 5),
+            Token::IDENT("b".into(), 1, 6),
+            Token::SPLICE(1, 8),
+            Token::IDENT("c".into(), 1, 10),
+            Token::RPAR(1, 11)
+        ];
+
+        let mut tokens = vec![];
+        while let Ok(token) = lexer.next() {
+            tokens.push(token)
+        }
+
+        assert_eq!(tokens, expected)
+    }
+
+    #[test]
+    fn read_quote() {
+        let mut lexer = StringLexer::new("'a '(1)".to_string());
+        let expected  = vec![
+            Token::QUOTE(1, 1),
+            Token::IDENT("a".into(), 1, 2),
+            Token::QUOTE(1, 4),
+            Token::LPAR(1, 5),
+            Token::INTEGER("1".to_string(), 1, 6),
+            Token::RPAR(1, 7)
+        ];
+
+        let mut tokens = vec![];
+        while let Ok(token) = lexer.next() {
+            tokens.push(token)
+        }
+
+        assert_eq!(tokens, expected)
+    }
+
+    #[test]
+    fn read_ellipsis() {
+        let mut lexer = StringLexer::new("(x ...)".to_string());
+        let expected  = vec![
+            Token::LPAR(1, 1),
+            Token::IDENT("x".into(), 1, 2),
+            Token::IDENT("...".into(), 1, 4),
+            Token::RPAR(1, 7)
+        ];
+        for token in expected {
+            assert_eq!(lexer.next().ok().unwrap(), token);
+        }
+        assert_eq!(StringLexer::new("1..".to_string()).next().err().unwrap(), LexError::FLOAT("1..".to_string(), 1, 1));
+    }
+
+    #[test]
+    fn read_dot() {
+        let mut lexer = StringLexer::new("(a . b) .5".to_string());
+        let expected  = vec![
+            Token::LPAR(1, 1),
+            Token::IDENT("a".into(), 1, 2),
+            Token::DOT(1, 4),
+            Token::IDENT("b".into(), 1, 6),
+            Token::RPAR(1, 7),
+            Token::FLOAT(".5".to_string(), 1, 9)
+        ];
+
+        let mut tokens = vec![];
+        while let Ok(token) = lexer.next() {
+            tokens.push(token)
+        }
+
+        assert_eq!(tokens, expected)
+    }
+
+    #[test]
+    fn error_hash() {
+        let mut lexer = StringLexer::new("#maybe".to_string());
+        assert_eq!(lexer.next().err().unwrap(), LexError::INVALID('#', 1, 1));
+    }
+
     #[test]
     fn error_invalid() {
         let mut lexer = StringLexer::new("(    # )".to_string());
@@ -385,17 +1174,57 @@ mod tests {
         assert_eq!(lexer.next().err().unwrap(), LexError::UNTERMINATED("This is an unterminated string ()".to_string(), 1, 1));
     }
 
+    #[test]
+    fn read_operator_idents() {
+        for op in &["+", "-", "*", "/", "<=", "=", "->x", "+inf", "!"] {
+            let mut lexer = StringLexer::new(op.to_string());
+            assert_eq!(lexer.next().ok().unwrap(), Token::IDENT((*op).into(), 1, 1));
+        }
+    }
+
+    #[test]
+    fn read_delimited() {
+        let mut lexer = StringLexer::new("(+ 1 -2.5)(a\"s\"b;c".to_string());
+        let expected  = vec![
+            Token::LPAR(1, 1),
+            Token::IDENT("+".into(), 1, 2),
+            Token::INTEGER("1".to_string(), 1, 4),
+            Token::FLOAT("-2.5".to_string(), 1, 6),
+            Token::RPAR(1, 10),
+            Token::LPAR(1, 11),
+            Token::IDENT("a".into(), 1, 12),
+            Token::STRING("s".into(), 1, 13),
+            Token::IDENT("b".into(), 1, 16),
+            Token::COMMENT(";c".into(), 1, 17)
+        ];
+
+        let mut tokens = vec![];
+        while let Ok(token) = lexer.next() {
+            tokens.push(token)
+        }
+
+        assert_eq!(tokens, expected)
+    }
+
+    #[test]
+    fn read_ident_with_slash() {
+        let mut lexer = StringLexer::new("call/cc a/b -/2".to_string());
+        assert_eq!(lexer.next(), Ok(Token::IDENT("call/cc".into(), 1, 1)));
+        assert_eq!(lexer.next(), Ok(Token::IDENT("a/b".into(), 1, 9)));
+        assert_eq!(lexer.next(), Ok(Token::IDENT("-/2".into(), 1, 13)));
+    }
+
     #[test]
     fn error_ident() {
-        let invalid = vec!['[', ']', '{', '}', '(', ')', '|', '\\', '/', '\'', '\"', '#', ','];
+        let invalid = vec!['[', ']', '{', '}', '|', '\\', '\'', '#', ','];
         let ident_pre = "an-ident-cannot-have-";
         let ident_suf = "-as-a-char";
 
         for i in invalid {
             let mut ident = String::new();
-            ident = ident + &ident_pre;
+            ident += ident_pre;
             ident.push(i);
-            ident = ident + &ident_suf;
+            ident += ident_suf;
 
             let mut lexer = StringLexer::new(ident);
             assert_eq!(lexer.next().err().unwrap(), LexError::IDENT(ident_pre.to_string(), 1, 1));
@@ -1,7 +1,14 @@
 pub use self::lexer::Lexer;
+pub use self::lexer::Token;
+pub use self::lexer::LexError;
+pub use self::lexer::LexerState;
+pub use self::lexer::CHAR_NAMES;
+pub use self::lexer::write_char;
 pub use self::string_lexer::StringLexer;
+pub use self::str_lexer::StrLexer;
 pub use self::io_lexer::IOLexer;
 
 mod lexer;
 mod string_lexer;
+mod str_lexer;
 mod io_lexer;
@@ -0,0 +1,257 @@
+use super::Lexer;
+use super::LexError;
+use super::LexerState;
+use std::str::CharIndices;
+
+#[derive(Clone)]
+pub struct StrLexer<'a> {
+    input:         &'a str,
+    chars:         CharIndices<'a>,
+    base:          usize,
+    current:       Option<(usize, char)>,
+    line:          u32,
+    chr:           u32,
+    skip_comments: bool,
+    fold_case:     bool
+}
+
+impl<'a> StrLexer<'a> {
+    pub fn new(input: &'a str) -> StrLexer<'a> {
+        let mut lexer = StrLexer { input, chars: input.char_indices(), base: 0, current: None, line: 1, chr: 1, skip_comments: false, fold_case: false };
+        lexer.advance();
+        lexer
+    }
+
+    /* `chars` iterates from `base`, so its indices are relative to it */
+    fn advance(&mut self) {
+        let base     = self.base;
+        self.current = self.chars.next().map(|(i, c)| (base + i, c));
+    }
+
+    /* byte offset of the next character to be read */
+    pub fn index(&self) -> usize {
+        match self.current {
+            Some((i, _)) => i,
+            None         => self.input.len()
+        }
+    }
+}
+
+impl<'a> Lexer<'a> for StrLexer<'a> {
+    fn get(&mut self) -> Option<char> {
+        match self.current {
+            Some((_, c)) => {
+                self.advance();
+                self.count(c);
+                Some(c)
+            },
+            None => None
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.current.map(|(_, c)| c)
+    }
+
+    fn set_line(&mut self, line: u32) {
+        self.line = line
+    }
+
+    fn set_chr(&mut self, chr: u32) {
+        self.chr = chr
+    }
+
+    fn line(&self) -> u32 {
+        self.line
+    }
+
+    fn chr(&self) -> u32 {
+        self.chr
+    }
+
+    fn skip_comments(&self) -> bool {
+        self.skip_comments
+    }
+
+    fn set_skip_comments(&mut self, skip: bool) {
+        self.skip_comments = skip
+    }
+
+    fn fold_case(&self) -> bool {
+        self.fold_case
+    }
+
+    fn set_fold_case(&mut self, fold: bool) {
+        self.fold_case = fold
+    }
+
+    fn slice(&self, start: usize, end: usize) -> Option<&'a str> {
+        Some(&self.input[start..end])
+    }
+
+    fn checkpoint(&self) -> LexerState {
+        LexerState { offset: self.index(), line: self.line, chr: self.chr }
+    }
+
+    fn restore(&mut self, state: LexerState) -> Result<(), LexError> {
+        self.chars = self.input[state.offset..].char_indices();
+        self.base  = state.offset;
+        self.line  = state.line;
+        self.chr   = state.chr;
+        self.advance();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::Lexer;
+    use lexer::lexer::Token;
+    use std::borrow::Cow;
+
+    static TEST_STRING : &str = "ab\ncd";
+
+    #[test]
+    fn new() {
+        let lexer = StrLexer::new(TEST_STRING);
+        assert_eq!(lexer.line, 1);
+        assert_eq!(lexer.chr, 1);
+        assert_eq!(lexer.index(), 0);
+    }
+
+    #[test]
+    fn peek() {
+        let mut lexer = StrLexer::new(TEST_STRING);
+        assert_eq!(Some('a'), lexer.peek());
+        assert_eq!(lexer.chr, 1);
+        assert_eq!(lexer.line, 1);
+        assert_eq!(Some('a'), lexer.peek());
+        assert_eq!(lexer.chr, 1);
+        assert_eq!(lexer.line, 1);
+        lexer.get();
+        assert_eq!(Some('b'), lexer.peek());
+        assert_eq!(lexer.chr, 2);
+        assert_eq!(lexer.line, 1);
+        assert_eq!(Some('b'), lexer.peek());
+        assert_eq!(lexer.chr, 2);
+        assert_eq!(lexer.line, 1);
+    }
+
+    #[test]
+    fn get() {
+        let mut lexer = StrLexer::new(TEST_STRING);
+        let result = lexer.get();
+        assert_eq!(Some('a'), result);
+        assert_eq!(lexer.chr, 2);
+        assert_eq!(lexer.line, 1);
+        assert_eq!(Some('b'), lexer.get());
+        assert_eq!(lexer.chr, 3);
+        assert_eq!(lexer.line, 1);
+        assert_eq!(Some('\n'), lexer.get());
+        assert_eq!(lexer.chr, 1);
+        assert_eq!(lexer.line, 2);
+        assert_eq!(Some('c'), lexer.get());
+        assert_eq!(lexer.chr, 2);
+        assert_eq!(lexer.line, 2);
+        assert_eq!(Some('d'), lexer.get());
+        assert_eq!(lexer.chr, 3);
+        assert_eq!(lexer.line, 2);
+        assert_eq!(None, lexer.get());
+    }
+
+    #[test]
+    fn get_multibyte() {
+        let mut lexer = StrLexer::new("λé\n");
+        assert_eq!(Some('λ'), lexer.get());
+        assert_eq!(lexer.chr, 2);
+        assert_eq!(lexer.index(), 2);
+        assert_eq!(Some('é'), lexer.get());
+        assert_eq!(lexer.chr, 3);
+        assert_eq!(lexer.index(), 4);
+        assert_eq!(Some('\n'), lexer.get());
+        assert_eq!(None, lexer.get());
+        assert_eq!(lexer.index(), 5);
+    }
+
+    #[test]
+    fn line() {
+        let mut lexer = StrLexer::new(TEST_STRING);
+        assert_eq!(lexer.line(), 1);
+        lexer.line = 43;
+        assert_eq!(lexer.line(), 43);
+    }
+
+    #[test]
+    fn set_line() {
+        let mut lexer = StrLexer::new(TEST_STRING);
+        assert_eq!(lexer.line(), 1);
+        lexer.set_line(43);
+        assert_eq!(lexer.line(), 43);
+    }
+
+    #[test]
+    fn chr() {
+        let mut lexer = StrLexer::new(TEST_STRING);
+        assert_eq!(lexer.chr(), 1);
+        lexer.chr = 43;
+        assert_eq!(lexer.chr(), 43);
+    }
+
+    #[test]
+    fn set_chr() {
+        let mut lexer = StrLexer::new(TEST_STRING);
+        assert_eq!(lexer.chr(), 1);
+        lexer.set_chr(43);
+        assert_eq!(lexer.chr(), 43);
+    }
+
+    #[test]
+    fn read_substring() {
+        let source    = "(ignored) \"λ string\" (";
+        let mut lexer = StrLexer::new(&source[10..]);
+        assert_eq!(lexer.next().ok().unwrap(), Token::STRING("λ string".into(), 1, 1));
+        assert_eq!(lexer.next().ok().unwrap(), Token::LPAR(1, 12));
+    }
+
+    #[test]
+    fn checkpoint_restore() {
+        let mut lexer = StrLexer::new("(id \"é\" 1)");
+        lexer.next().ok().unwrap();
+        let state  = lexer.checkpoint();
+        let first  = lexer.next().ok().unwrap();
+        let second = lexer.next().ok().unwrap();
+
+        assert_eq!(lexer.restore(state), Ok(()));
+        assert_eq!(lexer.index(), 1);
+        assert_eq!(lexer.next().ok().unwrap(), first);
+        assert_eq!(lexer.next().ok().unwrap(), second);
+        assert_eq!(second, Token::STRING("é".into(), 1, 5));
+    }
+
+    #[test]
+    fn borrows_token_text() {
+        let source    = "(foo bar-baz λ \"plain\" \"a \\\"b\\\"\") ; note\n".repeat(1000);
+        let mut lexer = StrLexer::new(&source);
+        let mut count = 0;
+        while let Ok(token) = lexer.next() {
+            match token {
+                Token::IDENT(Cow::Borrowed(_), ..)   |
+                Token::COMMENT(Cow::Borrowed(_), ..) => count += 1,
+                Token::STRING(Cow::Borrowed(s), ..)  => assert_eq!(s, "plain"),
+                Token::STRING(Cow::Owned(s), ..)     => assert_eq!(s, "a \"b\""),
+                Token::LPAR(..) | Token::RPAR(..)    => (),
+                token                                => panic!("copied token {:?}", token)
+            }
+        }
+        assert_eq!(count, 4000);
+    }
+
+    #[test]
+    fn folded_idents_are_owned() {
+        let mut lexer = StrLexer::new("Foo bar");
+        lexer.set_fold_case(true);
+        assert_eq!(lexer.next(), Ok(Token::IDENT(Cow::Owned("foo".to_string()), 1, 1)));
+        assert!(matches!(lexer.next(), Ok(Token::IDENT(Cow::Borrowed("bar"), 1, 5))));
+    }
+}
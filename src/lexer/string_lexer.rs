@@ -1,24 +1,29 @@
 use super::Lexer;
+use super::LexError;
+use super::LexerState;
 
+#[derive(Clone)]
 pub struct StringLexer {
-    input: Vec<u8>,
-    index: usize,
-    line:  u32,
-    chr:   u32
+    input:         String,
+    index:         usize,
+    line:          u32,
+    chr:           u32,
+    skip_comments: bool,
+    fold_case:     bool
 }
 
 
 impl StringLexer {
     pub fn new(input: String) -> StringLexer {
-        StringLexer { input: input.into_bytes(), index: 0, line: 1, chr: 1 }
+        StringLexer { input, index: 0, line: 1, chr: 1, skip_comments: false, fold_case: false }
     }
 }
 
-impl Lexer for StringLexer {
+impl<'a> Lexer<'a> for StringLexer {
     fn get(&mut self) -> Option<char> {
         match self.peek() {
             Some(c) => {
-                self.index = self.index + 1;
+                self.index += c.len_utf8();
                 self.count(c);
                 Some(c)
             },
@@ -26,19 +31,16 @@ impl Lexer for StringLexer {
         }
     }
 
+    /* `index` is a byte offset, always on a character boundary */
     fn peek(&self) -> Option<char> {
-        if self.index < self.input.len() {
-            Some(self.input[self.index] as char)
-        } else {
-            None
-        }
+        self.input[self.index..].chars().next()
     }
 
-    fn set_line(&mut self, line: u32) -> () {
+    fn set_line(&mut self, line: u32) {
         self.line = line
     }
 
-    fn set_chr(&mut self, chr: u32) -> () {
+    fn set_chr(&mut self, chr: u32) {
         self.chr = chr
     }
 
@@ -49,6 +51,33 @@ impl Lexer for StringLexer {
     fn chr(&self) -> u32 {
         self.chr
     }
+
+    fn skip_comments(&self) -> bool {
+        self.skip_comments
+    }
+
+    fn set_skip_comments(&mut self, skip: bool) {
+        self.skip_comments = skip
+    }
+
+    fn fold_case(&self) -> bool {
+        self.fold_case
+    }
+
+    fn set_fold_case(&mut self, fold: bool) {
+        self.fold_case = fold
+    }
+
+    fn checkpoint(&self) -> LexerState {
+        LexerState { offset: self.index, line: self.line, chr: self.chr }
+    }
+
+    fn restore(&mut self, state: LexerState) -> Result<(), LexError> {
+        self.index = state.offset;
+        self.line  = state.line;
+        self.chr   = state.chr;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -56,7 +85,7 @@ mod tests {
     use super::*;
     use lexer::Lexer;
 
-    static TEST_STRING : &'static str = "ab\ncd";
+    static TEST_STRING : &str = "ab\ncd";
 
     #[test]
     fn new() {
@@ -105,6 +134,36 @@ mod tests {
         assert_eq!(None, lexer.get());
     }
 
+    #[test]
+    fn get_multibyte() {
+        let mut lexer = StringLexer::new("λé\n".to_string());
+        assert_eq!(Some('λ'), lexer.get());
+        assert_eq!(lexer.chr, 2);
+        assert_eq!(Some('é'), lexer.peek());
+        assert_eq!(Some('é'), lexer.get());
+        assert_eq!(lexer.chr, 3);
+        assert_eq!(Some('\n'), lexer.get());
+        assert_eq!(None, lexer.get());
+    }
+
+    #[test]
+    fn clone_mid_stream() {
+        let mut lexer = StringLexer::new("(define x\n  \"λ\" 42)".to_string());
+        lexer.next().ok().unwrap();
+        lexer.next().ok().unwrap();
+
+        let mut copy = lexer.clone();
+        let mut rest = vec![];
+        while let Ok(token) = lexer.next() {
+            rest.push(token);
+        }
+        assert_eq!(rest.len(), 4);
+        for token in rest {
+            assert_eq!(copy.next(), Ok(token));
+        }
+        assert_eq!(copy.next(), lexer.next());
+    }
+
     #[test]
     fn line() {
         let mut lexer = StringLexer::new(TEST_STRING.to_string());
@@ -1,2 +1,14 @@
+#![allow(clippy::upper_case_acronyms, clippy::module_inception)]
+
+pub mod bigint;
 pub mod lexer;
 pub mod parser;
+pub mod rational;
+pub mod eval;
+pub mod program;
+pub mod repl;
+
+pub use parser::read_str;
+pub use program::run_file;
+pub use program::run_str;
+pub use repl::repl;
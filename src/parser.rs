@@ -1,17 +1,613 @@
+use bigint::BigInt;
+use lexer::IOLexer;
 use lexer::Lexer;
+use lexer::LexError;
+use lexer::StringLexer;
+use lexer::Token;
+use rational::Rational;
 
-#[derive(Debug)]
-pub struct Parser<T: Lexer> {
-    lexer: T
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::io::Read;
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Sexpr {
+    Integer(i64),
+    BigInt(BigInt),
+    Rational(Rational),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+    Char(char),
+    Symbol(String),
+    List(Vec<Sexpr>),
+    DottedList(Vec<Sexpr>, Box<Sexpr>),
+    Vector(Vec<Sexpr>)
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ParseError {
+    Lex(LexError),
+    Unexpected(Token<'static>),
+    Trailing(Token<'static>),
+    InvalidNumber(String, u32, u32),
+    UnclosedParen(u32, u32)
 }
 
-impl<T: Lexer> Parser<T> {
-    pub fn new(lexer: T) -> Parser<T> {
-        Parser { lexer: lexer }
+impl From<LexError> for ParseError {
+    fn from(err: LexError) -> ParseError {
+        ParseError::Lex(err)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::Lex(ref err)                    => write!(f, "{}", err),
+            ParseError::Unexpected(ref token)           => {
+                let (line, chr) = token.position();
+                write!(f, "unexpected '{}' at line {}, column {}", token, line, chr)
+            },
+            ParseError::Trailing(ref token)             => {
+                let (line, chr) = token.position();
+                write!(f, "trailing input '{}' at line {}, column {}", token, line, chr)
+            },
+            ParseError::InvalidNumber(ref s, line, chr) => write!(f, "number \"{}\" out of range at line {}, column {}", s, line, chr),
+            ParseError::UnclosedParen(line, chr)        => write!(f, "unclosed '(' at line {}, column {}", line, chr)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl ParseError {
+    pub fn position(&self) -> (u32, u32) {
+        match *self {
+            ParseError::Lex(ref err)                  => err.position(),
+            ParseError::Unexpected(ref token)         |
+            ParseError::Trailing(ref token)           => token.position(),
+            ParseError::InvalidNumber(_, line, chr)   |
+            ParseError::UnclosedParen(line, chr)      => (line, chr)
+        }
+    }
+}
+
+/* cloneable whenever its lexer is, for trying a parse without giving up the original */
+#[derive(Debug, Clone)]
+pub struct Parser<T> {
+    lexer:     T,
+    lookahead: VecDeque<Result<Token<'static>, LexError>>,
+    /* how many parens the tokens read so far have left open */
+    depth:     usize
+}
+
+/* tokens are made to own their text as they're read, the data parsed from them will anyway */
+impl<'a, T: Lexer<'a>> Parser<T> {
+    /* the lexer is switched to skipping comments, the parser never needs them */
+    pub fn new(mut lexer: T) -> Parser<T> {
+        lexer.set_skip_comments(true);
+        Parser { lexer, lookahead: VecDeque::new(), depth: 0 }
     }
 
     pub fn get_lexer(&mut self) -> &mut T {
         &mut self.lexer
     }
+
+    /* where the next token starts, or where the lexer has got to when none has been read ahead */
+    pub fn position(&self) -> (u32, u32) {
+        match self.lookahead.front() {
+            Some(Ok(token)) => token.position(),
+            Some(Err(err))  => err.position(),
+            None            => (self.lexer.line(), self.lexer.chr())
+        }
+    }
+
+    /* whether only whitespace and comments are left */
+    pub fn at_eof(&mut self) -> bool {
+        matches!(self.peek_nth(0), Some(Err(LexError::END(..))))
+    }
+
+    /* the token `n` places ahead, without consuming it. lexing stops at the end of the input, so
+       looking past the END error gives None */
+    pub fn peek_nth(&mut self, n: usize) -> Option<&Result<Token<'static>, LexError>> {
+        while self.lookahead.len() <= n {
+            if let Some(Err(LexError::END(..))) = self.lookahead.back() {
+                return None
+            }
+            let token = self.lexer.next().map(Token::into_owned);
+            self.lookahead.push_back(token);
+        }
+        self.lookahead.get(n)
+    }
+
+    /* read one datum, an empty input is reported as a LexError::END */
+    pub fn parse(&mut self) -> Result<Sexpr, ParseError> {
+        let token = self.next_token()?;
+        self.parse_token(token)
+    }
+
+    /* every datum that parses and every error met on the way, for tooling that wants all the
+       diagnostics at once. after an error the rest of the top-level form it was in is skipped, up to
+       the paren that closes it or an open paren at the start of a line, taken to begin the next form */
+    pub fn parse_all_recovering(&mut self) -> (Vec<Sexpr>, Vec<ParseError>) {
+        let mut data   = vec![];
+        let mut errors = vec![];
+        self.depth = 0;
+        loop {
+            match self.parse() {
+                Ok(datum)                               => data.push(datum),
+                Err(ParseError::Lex(LexError::END(..))) => return (data, errors),
+                Err(err)                                => {
+                    errors.push(err);
+                    self.synchronize();
+                }
+            }
+        }
+    }
+
+    fn synchronize(&mut self) {
+        while self.depth > 0 {
+            match self.peek_nth(0) {
+                Some(Ok(Token::LPAR(_, 1)))   |
+                Some(Ok(Token::VECTOR(_, 1))) |
+                Some(Err(LexError::END(..)))  |
+                None                          => break,
+                _                             => ()
+            }
+            let _ = self.next_token();
+        }
+        self.depth = 0;
+    }
+
+    fn parse_token(&mut self, token: Token<'static>) -> Result<Sexpr, ParseError> {
+        match token {
+            Token::LPAR(line, chr)        => self.parse_list(line, chr, true),
+            Token::VECTOR(line, chr)      => match self.parse_list(line, chr, false)? {
+                Sexpr::List(items) => Ok(Sexpr::Vector(items)),
+                _                  => unreachable!()
+            },
+            Token::STRING(s, _, _)        => Ok(Sexpr::Str(s.into_owned())),
+            Token::IDENT(s, _, _)         => Ok(Sexpr::Symbol(s.into_owned())),
+            Token::BOOLEAN(b, _, _)       => Ok(Sexpr::Bool(b)),
+            Token::CHAR(c, _, _)          => Ok(Sexpr::Char(c)),
+            Token::QUOTE(..)              => self.parse_abbreviation("quote"),
+            Token::QUASIQUOTE(..)         => self.parse_abbreviation("quasiquote"),
+            Token::UNQUOTE(..)            => self.parse_abbreviation("unquote"),
+            Token::SPLICE(..)             => self.parse_abbreviation("unquote-splicing"),
+            Token::INTEGER(s, line, chr)  => match s.parse() {
+                Ok(n)  => Ok(Sexpr::Integer(n)),
+                Err(_) => match BigInt::parse(&s) {
+                    Some(n) => Ok(Sexpr::BigInt(n)),
+                    None    => Err(ParseError::InvalidNumber(s, line, chr))
+                }
+            },
+            Token::RATIONAL(s, line, chr) => match Rational::parse(&s) {
+                Some(ref n) if n.is_integer() => Ok(integer(n.numer())),
+                Some(n)                       => Ok(Sexpr::Rational(n)),
+                None                          => Err(ParseError::InvalidNumber(s, line, chr))
+            },
+            Token::FLOAT(s, line, chr)    => match s.parse() {
+                Ok(n)  => Ok(Sexpr::Float(n)),
+                Err(_) => Err(ParseError::InvalidNumber(s, line, chr))
+            },
+            token                         => Err(ParseError::Unexpected(token))
+        }
+    }
+
+    /* 'x, `x, ,x and ,@x are read as (quote x), (quasiquote x), (unquote x) and (unquote-splicing x) */
+    fn parse_abbreviation(&mut self, name: &str) -> Result<Sexpr, ParseError> {
+        let datum = self.parse()?;
+        Ok(Sexpr::List(vec![Sexpr::Symbol(name.to_string()), datum]))
+    }
+
+    /* the opening paren at `line`, `chr` has already been consumed. running out of input before
+       its close is blamed on that paren */
+    fn parse_list(&mut self, line: u32, chr: u32, dotted: bool) -> Result<Sexpr, ParseError> {
+        match self.parse_items(dotted) {
+            Err(ParseError::Lex(LexError::END(..))) => Err(ParseError::UnclosedParen(line, chr)),
+            result                                  => result
+        }
+    }
+
+    /* vectors can't have a dotted tail */
+    fn parse_items(&mut self, dotted: bool) -> Result<Sexpr, ParseError> {
+        let mut items = vec![];
+        loop {
+            match self.next_token()? {
+                Token::RPAR(..)                               => return Ok(Sexpr::List(items)),
+                Token::DOT(..) if dotted && !items.is_empty() => return self.parse_tail(items),
+                token                                         => items.push(self.parse_token(token)?)
+            }
+        }
+    }
+
+    /* the datum after a dot, which must be the last in the list */
+    fn parse_tail(&mut self, items: Vec<Sexpr>) -> Result<Sexpr, ParseError> {
+        let tail = self.parse()?;
+        match self.next_token()? {
+            Token::RPAR(..) => Ok(Sexpr::DottedList(items, Box::new(tail))),
+            token           => Err(ParseError::Unexpected(token))
+        }
+    }
+
+    /* consume the remaining input, failing if anything but the end is left */
+    fn expect_end(&mut self) -> Result<(), ParseError> {
+        match self.next_token() {
+            Ok(token)              => Err(ParseError::Trailing(token)),
+            Err(LexError::END(..)) => Ok(()),
+            Err(err)               => Err(ParseError::Lex(err))
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Token<'static>, LexError> {
+        let token = match self.lookahead.pop_front() {
+            Some(token) => token,
+            None        => self.lexer.next().map(Token::into_owned)
+        };
+        match token {
+            Ok(Token::LPAR(..)) | Ok(Token::VECTOR(..)) => self.depth += 1,
+            Ok(Token::RPAR(..))                         => self.depth = self.depth.saturating_sub(1),
+            _                                           => ()
+        }
+        token
+    }
+}
+
+/* each datum in turn until the input runs out. errors are yielded and parsing carries on after them */
+impl<'a, T: Lexer<'a>> Iterator for Parser<T> {
+    type Item = Result<Sexpr, ParseError>;
+
+    fn next(&mut self) -> Option<Result<Sexpr, ParseError>> {
+        match self.parse() {
+            Err(ParseError::Lex(LexError::END(..))) => None,
+            result                                  => Some(result)
+        }
+    }
+}
+
+/* the smallest representation of a whole number */
+fn integer(n: &BigInt) -> Sexpr {
+    match n.to_i64() {
+        Some(n) => Sexpr::Integer(n),
+        None    => Sexpr::BigInt(n.clone())
+    }
+}
+
+/* parse exactly one datum from `input`, erroring on anything after it */
+pub fn read_str(input: &str) -> Result<Sexpr, ParseError> {
+    let mut parser = Parser::new(StringLexer::new(input.to_string()));
+    let datum      = parser.parse()?;
+    parser.expect_end()?;
+    Ok(datum)
+}
+
+/* parse `input` lazily, one datum at a time, reading only as much of it as each datum needs */
+pub fn parse_stream<R: Read>(input: R) -> impl Iterator<Item = Result<Sexpr, ParseError>> {
+    Parser::new(IOLexer::new(input))
+}
+
+/* the error's message followed by the line of `source` it happened on, with a caret under its column */
+pub fn render_error(source: &str, err: &LexError) -> String {
+    let (line, chr) = err.position();
+    snippet(source, &err.to_string(), line, chr)
+}
+
+pub fn render_parse_error(source: &str, err: &ParseError) -> String {
+    let (line, chr) = err.position();
+    snippet(source, &err.to_string(), line, chr)
+}
+
+/* an error at the end of the input may be on a line, or at a column, past what `source` has */
+fn snippet(source: &str, message: &str, line: u32, chr: u32) -> String {
+    let text       = source.lines().nth(line as usize - 1).unwrap_or("");
+    let number     = line.to_string();
+    let gutter     = " ".repeat(number.len());
+    let mut indent = text.chars().take(chr as usize - 1).map(|c| if c == '\t' { '\t' } else { ' ' }).collect::<String>();
+    while indent.chars().count() < chr as usize - 1 {
+        indent.push(' ');
+    }
+    format!("{}\n{} |\n{} | {}\n{} | {}^", message, gutter, number, text, gutter, indent)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    fn symbol(s: &str) -> Sexpr {
+        Sexpr::Symbol(s.to_string())
+    }
+
+    #[test]
+    fn parse_atoms() {
+        assert_eq!(read_str("42"), Ok(Sexpr::Integer(42)));
+        assert_eq!(read_str("-1.5"), Ok(Sexpr::Float(-1.5)));
+        assert_eq!(read_str("\"hi\""), Ok(Sexpr::Str("hi".to_string())));
+        assert_eq!(read_str("foo"), Ok(symbol("foo")));
+        assert_eq!(read_str("#f"), Ok(Sexpr::Bool(false)));
+    }
+
+    #[test]
+    fn parse_list() {
+        assert_eq!(read_str("(+ 1 2)"), Ok(Sexpr::List(vec![symbol("+"), Sexpr::Integer(1), Sexpr::Integer(2)])));
+    }
+
+    #[test]
+    fn parse_nested_list() {
+        let expected = Sexpr::List(vec![
+            symbol("define"),
+            Sexpr::List(vec![symbol("f"), symbol("x")]),
+            Sexpr::List(vec![])
+        ]);
+        assert_eq!(read_str("(define (f x) ; body\n ())"), Ok(expected));
+    }
+
+    #[test]
+    fn parse_quote() {
+        assert_eq!(read_str("'a"), Ok(Sexpr::List(vec![symbol("quote"), symbol("a")])));
+        assert_eq!(read_str("'()"), Ok(Sexpr::List(vec![symbol("quote"), Sexpr::List(vec![])])));
+        assert_eq!(read_str("(f 'x)"), Ok(Sexpr::List(vec![
+            symbol("f"),
+            Sexpr::List(vec![symbol("quote"), symbol("x")])
+        ])));
+    }
+
+    #[test]
+    fn parse_quasiquote() {
+        assert_eq!(read_str("`(a ,b ,@c)"), Ok(Sexpr::List(vec![symbol("quasiquote"), Sexpr::List(vec![
+            symbol("a"),
+            Sexpr::List(vec![symbol("unquote"), symbol("b")]),
+            Sexpr::List(vec![symbol("unquote-splicing"), symbol("c")])
+        ])])));
+    }
+
+    #[test]
+    fn parse_dotted_list() {
+        assert_eq!(read_str("(a . b)"), Ok(Sexpr::DottedList(vec![symbol("a")], Box::new(symbol("b")))));
+        assert_eq!(read_str("(a b . (c))"), Ok(Sexpr::DottedList(
+            vec![symbol("a"), symbol("b")],
+            Box::new(Sexpr::List(vec![symbol("c")]))
+        )));
+    }
+
+    #[test]
+    fn error_dotted_list() {
+        assert_eq!(read_str("(. a)"), Err(ParseError::Unexpected(Token::DOT(1, 2))));
+        assert_eq!(read_str("(a . b c)"), Err(ParseError::Unexpected(Token::IDENT("c".into(), 1, 8))));
+        assert_eq!(read_str("(a . )"), Err(ParseError::Unexpected(Token::RPAR(1, 6))));
+    }
+
+    #[test]
+    fn parse_char() {
+        assert_eq!(read_str("(#\\a #\\))"), Ok(Sexpr::List(vec![Sexpr::Char('a'), Sexpr::Char(')')])));
+    }
+
+    #[test]
+    fn parse_vector() {
+        assert_eq!(read_str("#(1 (a) #())"), Ok(Sexpr::Vector(vec![Sexpr::Integer(1), Sexpr::List(vec![symbol("a")]), Sexpr::Vector(vec![])])));
+        assert_eq!(read_str("'#(a)"), Ok(Sexpr::List(vec![symbol("quote"), Sexpr::Vector(vec![symbol("a")])])));
+        assert_eq!(read_str("#(a . b)"), Err(ParseError::Unexpected(Token::DOT(1, 5))));
+        assert_eq!(read_str("#(a"), Err(ParseError::UnclosedParen(1, 1)));
+    }
+
+    #[test]
+    fn parse_skips_comments() {
+        assert_eq!(read_str("; leading\n1 ; trailing"), Ok(Sexpr::Integer(1)));
+        assert_eq!(read_str("(1 #| two |# #;(3 4) 5)"), Ok(Sexpr::List(vec![Sexpr::Integer(1), Sexpr::Integer(5)])));
+        assert_eq!(read_str("(1 #;#(a) 2)"), Ok(Sexpr::List(vec![Sexpr::Integer(1), Sexpr::Integer(2)])));
+        assert_eq!(read_str("#;#(1 (2)) 3"), Ok(Sexpr::Integer(3)));
+        assert_eq!(read_str("(list #;#(1 2) 3)"), Ok(Sexpr::List(vec![symbol("list"), Sexpr::Integer(3)])));
+    }
+
+    #[test]
+    fn parse_successive() {
+        let mut parser = Parser::new(StringLexer::new("(a) b".to_string()));
+        assert_eq!(parser.parse(), Ok(Sexpr::List(vec![symbol("a")])));
+        assert_eq!(parser.parse(), Ok(symbol("b")));
+        assert_eq!(parser.parse(), Err(ParseError::Lex(LexError::END(1, 6))));
+    }
+
+    #[test]
+    fn parse_clone() {
+        let mut parser = Parser::new(StringLexer::new("(a) ; note\n b".to_string()));
+        assert_eq!(parser.parse(), Ok(Sexpr::List(vec![symbol("a")])));
+        assert_eq!(parser.peek_nth(0), Some(&Ok(Token::IDENT("b".into(), 2, 2))));
+
+        let mut speculative = parser.clone();
+        assert_eq!(speculative.parse(), Ok(symbol("b")));
+        assert!(speculative.parse().is_err());
+        assert_eq!(parser.parse(), Ok(symbol("b")));
+    }
+
+    #[test]
+    fn peek_nth() {
+        let mut parser = Parser::new(StringLexer::new("(a . b)".to_string()));
+        assert_eq!(parser.peek_nth(2), Some(&Ok(Token::DOT(1, 4))));
+        assert_eq!(parser.peek_nth(0), Some(&Ok(Token::LPAR(1, 1))));
+        assert_eq!(parser.peek_nth(1), Some(&Ok(Token::IDENT("a".into(), 1, 2))));
+        assert_eq!(parser.parse(), Ok(Sexpr::DottedList(vec![symbol("a")], Box::new(symbol("b")))));
+    }
+
+    #[test]
+    fn peek_nth_at_end() {
+        let mut parser = Parser::new(StringLexer::new("a ; done".to_string()));
+        assert_eq!(parser.peek_nth(1), Some(&Err(LexError::END(1, 9))));
+        assert_eq!(parser.peek_nth(5), None);
+        assert_eq!(parser.parse(), Ok(symbol("a")));
+        assert_eq!(parser.parse(), Err(ParseError::Lex(LexError::END(1, 9))));
+        assert_eq!(parser.peek_nth(0), Some(&Err(LexError::END(1, 9))));
+    }
+
+    #[test]
+    fn position_and_eof() {
+        let mut parser = Parser::new(StringLexer::new("(a)".to_string()));
+        assert_eq!(parser.position(), (1, 1));
+        assert!(!parser.at_eof());
+        assert_eq!(parser.parse(), Ok(Sexpr::List(vec![symbol("a")])));
+        assert_eq!(parser.position(), (1, 4));
+        assert!(parser.at_eof());
+    }
+
+    #[test]
+    fn position_mid_parse() {
+        let mut parser = Parser::new(StringLexer::new("1\n  ; note\n  (b c) ; end\n".to_string()));
+        assert_eq!(parser.parse(), Ok(Sexpr::Integer(1)));
+        assert!(!parser.at_eof());
+        assert_eq!(parser.position(), (3, 3));
+        assert_eq!(parser.parse(), Ok(Sexpr::List(vec![symbol("b"), symbol("c")])));
+        assert_eq!(parser.position(), (3, 8));
+        assert!(parser.at_eof());
+        assert_eq!(parser.position(), (4, 1));
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn parse_iterator() {
+        let parser = Parser::new(StringLexer::new("1 (a) ) b".to_string()));
+        assert_eq!(parser.collect::<Vec<_>>(), vec![
+            Ok(Sexpr::Integer(1)),
+            Ok(Sexpr::List(vec![symbol("a")])),
+            Err(ParseError::Unexpected(Token::RPAR(1, 7))),
+            Ok(symbol("b"))
+        ]);
+    }
+
+    /* hands out its chunks one read at a time and panics if asked for more */
+    struct Chunks(Vec<&'static str>);
+
+    impl Read for Chunks {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                panic!("read past the data needed");
+            }
+            let chunk = self.0.remove(0).as_bytes();
+            buf[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn parse_all_recovering() {
+        let mut parser     = Parser::new(StringLexer::new("(a (b) ) ) (c)".to_string()));
+        let (data, errors) = parser.parse_all_recovering();
+        assert_eq!(data, vec![read_str("(a (b))").unwrap(), read_str("(c)").unwrap()]);
+        assert_eq!(errors, vec![ParseError::Unexpected(Token::RPAR(1, 10))]);
+    }
+
+    #[test]
+    fn recovers_inside_forms() {
+        let mut parser     = Parser::new(StringLexer::new("(a (b ]) c)\n(d . e f (g)\n(h)\n(i".to_string()));
+        let (data, errors) = parser.parse_all_recovering();
+        assert_eq!(data, vec![read_str("(h)").unwrap()]);
+        assert_eq!(errors, vec![
+            ParseError::Lex(LexError::INVALID(']', 1, 7)),
+            ParseError::Unexpected(Token::IDENT("f".into(), 2, 8)),
+            ParseError::UnclosedParen(4, 1)
+        ]);
+    }
+
+    #[test]
+    fn parse_stream_lazily() {
+        let mut forms = parse_stream(Chunks(vec!["(define x\n", "  42)\n"]));
+        let first     = Sexpr::List(vec![symbol("define"), symbol("x"), Sexpr::Integer(42)]);
+        assert_eq!(forms.next(), Some(Ok(first)));
+
+        let forms = parse_stream(Chunks(vec!["1 (a", " b) ", ""]));
+        assert_eq!(forms.count(), 2);
+    }
+
+    #[test]
+    fn error_trailing() {
+        assert_eq!(read_str("1 2"), Err(ParseError::Trailing(Token::INTEGER("2".to_string(), 1, 3))));
+    }
+
+    #[test]
+    fn error_unexpected_rpar() {
+        assert_eq!(read_str(")"), Err(ParseError::Unexpected(Token::RPAR(1, 1))));
+    }
+
+    #[test]
+    fn error_unclosed() {
+        assert_eq!(read_str("(a (b)"), Err(ParseError::UnclosedParen(1, 1)));
+        assert_eq!(read_str("(a\n  (b c"), Err(ParseError::UnclosedParen(2, 3)));
+        assert_eq!(read_str("(a . "), Err(ParseError::UnclosedParen(1, 1)));
+        assert_eq!(read_str("'(a '"), Err(ParseError::UnclosedParen(1, 2)));
+    }
+
+    #[test]
+    fn error_unclosed_position() {
+        assert_eq!(read_str("(a (b c)"), Err(ParseError::UnclosedParen(1, 1)));
+        assert_eq!(read_str("(a (b c)").err().unwrap().to_string(), "unclosed '(' at line 1, column 1");
+    }
+
+    #[test]
+    fn error_extra_rpar() {
+        assert_eq!(read_str("(a))"), Err(ParseError::Trailing(Token::RPAR(1, 4))));
+
+        let mut parser = Parser::new(StringLexer::new("(a))".to_string()));
+        assert_eq!(parser.parse(), Ok(Sexpr::List(vec![symbol("a")])));
+        assert_eq!(parser.parse(), Err(ParseError::Unexpected(Token::RPAR(1, 4))));
+    }
+
+    #[test]
+    fn error_empty() {
+        assert_eq!(read_str(""), Err(ParseError::Lex(LexError::END(1, 1))));
+    }
+
+    #[test]
+    fn error_lex() {
+        assert_eq!(read_str("(12f)"), Err(ParseError::Lex(LexError::INTEGER("12f".to_string(), 1, 2))));
+    }
+
+    #[test]
+    fn parse_rational() {
+        assert_eq!(read_str("3/4"), Ok(Sexpr::Rational(Rational::parse("3/4").unwrap())));
+        assert_eq!(read_str("6/8"), Ok(Sexpr::Rational(Rational::parse("3/4").unwrap())));
+        assert_eq!(read_str("6/3"), Ok(Sexpr::Integer(2)));
+        assert_eq!(read_str("1/0"), Err(ParseError::InvalidNumber("1/0".to_string(), 1, 1)));
+    }
+
+    #[test]
+    fn parse_bigint() {
+        assert_eq!(read_str("99999999999999999999"), Ok(Sexpr::BigInt(BigInt::parse("99999999999999999999").unwrap())));
+        assert_eq!(read_str("-9223372036854775808"), Ok(Sexpr::Integer(i64::MIN)));
+        assert_eq!(read_str("-9223372036854775809"), Ok(Sexpr::BigInt(BigInt::parse("-9223372036854775809").unwrap())));
+    }
+
+    #[test]
+    fn render_invalid_char() {
+        let source = "(define x 1)\n(list a [b c)\n";
+        let err    = Parser::new(StringLexer::new(source.to_string())).nth(1).unwrap().err().unwrap();
+        assert_eq!(err, ParseError::Lex(LexError::INVALID('[', 2, 9)));
+        assert_eq!(render_parse_error(source, &err), "\
+invalid character '[' at line 2, column 9
+  |
+2 | (list a [b c)
+  |         ^");
+        assert_eq!(render_error(source, &LexError::INVALID('[', 2, 9)), render_parse_error(source, &err));
+    }
+
+    #[test]
+    fn render_keeps_tabs() {
+        let rendered = render_error("\t\tx @", &LexError::INVALID('@', 1, 5));
+        assert_eq!(rendered.lines().last(), Some("  | \t\t  ^"));
+    }
+
+    #[test]
+    fn render_at_eof() {
+        let err = read_str("(a b").err().unwrap();
+        assert_eq!(render_parse_error("(a b", &err), "unclosed '(' at line 1, column 1\n  |\n1 | (a b\n  | ^");
+
+        let rendered = render_error("(a b", &LexError::END(1, 5));
+        assert_eq!(rendered.lines().last(), Some("  |     ^"));
+
+        let rendered = render_error("\"abc\n", &LexError::END(2, 1));
+        assert_eq!(rendered, "unexpected end of input at line 2, column 1\n  |\n2 | \n  | ^");
+    }
+
+    #[test]
+    fn display_error() {
+        assert_eq!(read_str("1 2").err().unwrap().to_string(), "trailing input '2' at line 1, column 3");
+    }
+}
@@ -0,0 +1,120 @@
+use eval::eval;
+use eval::Environment;
+use eval::EvalError;
+use eval::Value;
+use lexer::IOLexer;
+use lexer::Lexer;
+use lexer::StrLexer;
+use parser::ParseError;
+use parser::Parser;
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::path::Path;
+
+/* why a program couldn't be run, with where in its source that happened */
+#[derive(PartialEq, Debug)]
+pub enum ProgramError {
+    Io(String),
+    Parse(ParseError),
+    /* the error from evaluating the top-level form starting at the line and column */
+    Eval(EvalError, u32, u32)
+}
+
+impl ProgramError {
+    pub fn position(&self) -> Option<(u32, u32)> {
+        match *self {
+            ProgramError::Io(_)              => None,
+            ProgramError::Parse(ref err)     => Some(err.position()),
+            ProgramError::Eval(_, line, chr) => Some((line, chr))
+        }
+    }
+}
+
+impl From<ParseError> for ProgramError {
+    fn from(err: ParseError) -> ProgramError {
+        ProgramError::Parse(err)
+    }
+}
+
+impl fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProgramError::Io(ref message)          => write!(f, "i/o error: {}", message),
+            ProgramError::Parse(ref err)           => write!(f, "{}", err),
+            ProgramError::Eval(ref err, line, chr) => write!(f, "{} in the form at line {}, column {}", err, line, chr)
+        }
+    }
+}
+
+impl Error for ProgramError {}
+
+/* run the program in the file at `path`, see run_str */
+pub fn run_file(path: &Path, env: &mut Environment) -> Result<Value, ProgramError> {
+    let file = File::open(path).map_err(|err| ProgramError::Io(format!("{}: {}", path.display(), err)))?;
+    run(Parser::new(IOLexer::new(file)), env)
+}
+
+/* evaluate each top-level form of `source` in turn against `env`, so later forms see what
+   earlier ones define, giving the value of the last one. the whole program is parsed before
+   any of it runs, and the first error stops it */
+pub fn run_str(source: &str, env: &mut Environment) -> Result<Value, ProgramError> {
+    run(Parser::new(StrLexer::new(source)), env)
+}
+
+fn run<'a, T: Lexer<'a>>(mut parser: Parser<T>, env: &mut Environment) -> Result<Value, ProgramError> {
+    let mut forms = vec![];
+    while !parser.at_eof() {
+        let (line, chr) = parser.position();
+        forms.push((parser.parse()?, line, chr));
+    }
+
+    let mut value = Value::Nil;
+    for (form, line, chr) in forms {
+        value = eval(&form, env).map_err(|err| ProgramError::Eval(err, line, chr))?;
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::LexError;
+
+    #[test]
+    fn runs_forms_in_order() {
+        let mut env = Environment::global();
+        let source  = "(define x 2)\n(define (double n) (* n 2))\n(double x)\n";
+        assert_eq!(run_str(source, &mut env), Ok(Value::Integer(4)));
+        assert_eq!(env.get("x"), Some(Value::Integer(2)));
+        assert_eq!(run_str("; nothing\n", &mut env), Ok(Value::Nil));
+    }
+
+    #[test]
+    fn eval_error_position() {
+        let mut env = Environment::global();
+        let result  = run_str("(define x 1)\n\n  (car x)\n(define y 2)", &mut env);
+        assert_eq!(result, Err(ProgramError::Eval(EvalError::TypeError("car: not a pair: Integer(1)".to_string()), 3, 3)));
+        assert_eq!(env.get("y"), None);
+    }
+
+    #[test]
+    fn parse_error_runs_nothing() {
+        let mut env = Environment::global();
+        let result  = run_str("(define x 1)\n(define y", &mut env);
+        assert_eq!(result, Err(ProgramError::Parse(ParseError::UnclosedParen(2, 1))));
+        assert_eq!(result.unwrap_err().position(), Some((2, 1)));
+        assert_eq!(env.get("x"), None);
+
+        let result = run_str("(define x 1)\n]", &mut env);
+        assert_eq!(result, Err(ProgramError::Parse(ParseError::Lex(LexError::INVALID(']', 2, 1)))));
+    }
+
+    #[test]
+    fn missing_file() {
+        let mut env = Environment::global();
+        let result  = run_file(Path::new("no/such/program.scm"), &mut env);
+        assert!(matches!(result, Err(ProgramError::Io(ref message)) if message.starts_with("no/such/program.scm: ")));
+    }
+}
@@ -0,0 +1,137 @@
+use bigint::BigInt;
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/* an exact fraction in lowest terms, the denominator is always positive */
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Rational {
+    numer: BigInt,
+    denom: BigInt
+}
+
+impl Rational {
+    /* None when dividing by zero */
+    pub fn new(numer: BigInt, denom: BigInt) -> Option<Rational> {
+        if denom.is_zero() {
+            return None
+        }
+
+        let divisor = numer.gcd(&denom);
+        let (numer, _) = numer.div_rem(&divisor).unwrap();
+        let (denom, _) = denom.div_rem(&divisor).unwrap();
+        if denom.is_negative() {
+            Some(Rational { numer: numer.neg(), denom: denom.neg() })
+        } else {
+            Some(Rational { numer, denom })
+        }
+    }
+
+    pub fn from_integer(n: BigInt) -> Rational {
+        Rational { numer: n, denom: BigInt::one() }
+    }
+
+    /* a fraction written as `n/m`, with an optional sign on `n` */
+    pub fn parse(s: &str) -> Option<Rational> {
+        let slash = s.find('/')?;
+        Rational::new(BigInt::parse(&s[..slash])?, BigInt::parse(&s[slash + 1..])?)
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        &self.numer
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        &self.denom
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == BigInt::one()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numer.to_f64() / self.denom.to_f64()
+    }
+
+    pub fn add(&self, other: &Rational) -> Rational {
+        let numer = self.numer.mul(&other.denom).add(&other.numer.mul(&self.denom));
+        Rational::new(numer, self.denom.mul(&other.denom)).unwrap()
+    }
+
+    pub fn sub(&self, other: &Rational) -> Rational {
+        let numer = self.numer.mul(&other.denom).sub(&other.numer.mul(&self.denom));
+        Rational::new(numer, self.denom.mul(&other.denom)).unwrap()
+    }
+
+    pub fn mul(&self, other: &Rational) -> Rational {
+        Rational::new(self.numer.mul(&other.numer), self.denom.mul(&other.denom)).unwrap()
+    }
+
+    /* None when `other` is zero */
+    pub fn div(&self, other: &Rational) -> Option<Rational> {
+        Rational::new(self.numer.mul(&other.denom), self.denom.mul(&other.numer))
+    }
+}
+
+/* denominators are positive, so cross multiplying keeps the order */
+impl Ord for Rational {
+    fn cmp(&self, other: &Rational) -> Ordering {
+        self.numer.mul(&other.denom).cmp(&other.numer.mul(&self.denom))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(s: &str) -> Rational {
+        Rational::parse(s).unwrap()
+    }
+
+    #[test]
+    fn lowest_terms() {
+        assert_eq!(ratio("6/8").to_string(), "3/4");
+        assert_eq!(ratio("3/-6").to_string(), "-1/2");
+        assert_eq!(ratio("-4/-2").to_string(), "2");
+        assert_eq!(ratio("0/5"), Rational::from_integer(BigInt::zero()));
+        assert_eq!(Rational::parse("1/0"), None);
+        assert_eq!(Rational::parse("1"), None);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(ratio("1/2").add(&ratio("1/3")), ratio("5/6"));
+        assert_eq!(ratio("1/2").sub(&ratio("1/3")), ratio("1/6"));
+        assert_eq!(ratio("2/3").mul(&ratio("3/4")), ratio("1/2"));
+        assert_eq!(ratio("1/2").div(&ratio("1/4")), Some(ratio("2/1")));
+        assert_eq!(ratio("1/2").div(&ratio("0/1")), None);
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(ratio("1/3") < ratio("1/2"));
+        assert!(ratio("-1/2") < ratio("-1/3"));
+        assert_eq!(ratio("2/4").cmp(&ratio("1/2")), Ordering::Equal);
+    }
+
+    #[test]
+    fn to_f64() {
+        assert_eq!(ratio("1/4").to_f64(), 0.25);
+    }
+}
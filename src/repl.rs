@@ -0,0 +1,36 @@
+use eval::eval;
+use eval::Environment;
+use eval::EvalError;
+use lexer::IOLexer;
+use lexer::LexError;
+use parser::ParseError;
+use parser::Parser;
+
+use std::io;
+use std::io::Read;
+
+const PROMPT: &str = "> ";
+
+/* read, evaluate and print each datum from `input` until it runs out, printing through `env`'s
+   output sink. errors are printed and the loop carries on, only a failure to print stops it */
+pub fn repl<R: Read>(input: R, env: &mut Environment) -> io::Result<()> {
+    let mut parser = Parser::new(IOLexer::new(input));
+    loop {
+        env.write_output(PROMPT)?;
+        let datum = match parser.parse() {
+            Ok(datum)                               => datum,
+            Err(ParseError::Lex(LexError::END(..))) => return env.write_output("\n"),
+            Err(err)                                => {
+                env.write_output(&format!("error: {}\n", err))?;
+                continue
+            }
+        };
+
+        /* errors raised by the program with `error` are told apart from the interpreter's own */
+        match eval(&datum, env) {
+            Ok(value)                              => env.write_output(&format!("{}\n", value))?,
+            Err(err @ EvalError::UserError { .. }) => env.write_output(&format!("user error: {}\n", err))?,
+            Err(err)                               => env.write_output(&format!("error: {}\n", err))?
+        }
+    }
+}
@@ -0,0 +1,17 @@
+extern crate scheme;
+
+use scheme::lexer::Lexer;
+use scheme::lexer::StringLexer;
+use scheme::lexer::Token;
+
+/* the crate's only lexer is the one in src/lexer/, whose tokens carry positions and know about floats */
+#[test]
+fn exposes_positioned_tokens() {
+    let mut lexer = StringLexer::new("(1.5)".to_string());
+    assert_eq!(lexer.next(), Ok(Token::LPAR(1, 1)));
+    match lexer.next() {
+        Ok(Token::FLOAT(ref s, 1, 2)) => assert_eq!(s, "1.5"),
+        other                         => panic!("expected a float, got {:?}", other)
+    }
+    assert_eq!(lexer.next(), Ok(Token::RPAR(1, 5)));
+}
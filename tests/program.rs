@@ -0,0 +1,14 @@
+extern crate scheme;
+
+use scheme::eval::Environment;
+use scheme::eval::Value;
+
+use std::path::Path;
+
+#[test]
+fn runs_a_program_file() {
+    let path    = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/programs/factorial.scm");
+    let mut env = Environment::global();
+    assert_eq!(scheme::run_file(&path, &mut env), Ok(Value::Integer(3628800)));
+    assert_eq!(env.get("ten"), Some(Value::Integer(10)));
+}
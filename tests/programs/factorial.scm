@@ -0,0 +1,10 @@
+; computes 10! by way of a helper defined before it's used
+(define (factorial n)
+  (let loop ((i n) (acc 1))
+    (if (= i 0)
+        acc
+        (loop (- i 1) (* acc i)))))
+
+(define ten 10)
+
+(factorial ten)
@@ -0,0 +1,79 @@
+extern crate scheme;
+
+use scheme::eval::Environment;
+
+use std::cell::RefCell;
+use std::io;
+use std::io::Write;
+use std::rc::Rc;
+
+#[derive(Clone)]
+struct Capture(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/* run a REPL session over `input`, returning everything it printed */
+fn session(input: &str) -> String {
+    let buffer  = Capture(Rc::new(RefCell::new(vec![])));
+    let mut env = Environment::global();
+    env.set_output(buffer.clone());
+    scheme::repl(input.as_bytes(), &mut env).unwrap();
+
+    let output = buffer.0.borrow().clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn evaluates_each_datum() {
+    let output = session("(+ 1 2)\n(list 1 \"a\" 2.0)\n");
+    assert_eq!(output, "> 3\n> (1 \"a\" 2.0)\n> \n");
+}
+
+#[test]
+fn keeps_definitions() {
+    let output = session("(define (square x) (* x x))\n(square 12)\n");
+    assert_eq!(output, "> square\n> 144\n> \n");
+}
+
+#[test]
+fn continues_after_errors() {
+    let output = session("(car 1)\nundefined\n)\n(+ 1 1)\n");
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("> error: type error"));
+    assert_eq!(lines[1], "> error: unbound variable: undefined");
+    assert_eq!(lines[2], "> error: unexpected ')' at line 3, column 1");
+    assert_eq!(lines[3], "> 2");
+    assert_eq!(lines[4], "> ");
+}
+
+#[test]
+fn prints_user_errors() {
+    let output = session("(error \"boom\" 1 'x)\n(guard (e (#t 'recovered)) (error \"boom\"))\n");
+    assert_eq!(output, "> user error: boom 1 x\n> recovered\n> \n");
+}
+
+#[test]
+fn stops_at_eof() {
+    assert_eq!(session(""), "> \n");
+    assert_eq!(session("1"), "> 1\n> \n");
+}
+
+#[test]
+fn reports_unclosed_list_at_eof() {
+    assert_eq!(session("1\n(+ 1\n  (* 2 3)\n"), "> 1\n> error: unclosed '(' at line 2, column 1\n> \n");
+}
+
+#[test]
+fn prints_output_in_order() {
+    let output = session("(display \"hi\")\n");
+    assert_eq!(output, "> hi()\n> \n");
+}